@@ -0,0 +1,43 @@
+-- Platform targeting, external command requirements and per-platform archives
+
+-- Platforms (os/arch) a plugin version supports. A version without rows runs anywhere;
+-- a NULL arch means every architecture of that OS.
+CREATE TABLE IF NOT EXISTS plugin_platforms (
+    id SERIAL PRIMARY KEY,
+    plugin_id VARCHAR(255) NOT NULL REFERENCES plugins(id) ON DELETE CASCADE,
+    version VARCHAR(50) NOT NULL,
+    os VARCHAR(50) NOT NULL,
+    arch VARCHAR(50)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_plugin_platforms_unique
+    ON plugin_platforms(plugin_id, version, os, COALESCE(arch, ''));
+CREATE INDEX IF NOT EXISTS idx_plugin_platforms_os ON plugin_platforms(os, arch);
+
+-- External commands (e.g. systemctl, brew) a plugin version needs on the host
+CREATE TABLE IF NOT EXISTS plugin_requirements (
+    id SERIAL PRIMARY KEY,
+    plugin_id VARCHAR(255) NOT NULL REFERENCES plugins(id) ON DELETE CASCADE,
+    version VARCHAR(50) NOT NULL,
+    command VARCHAR(255) NOT NULL,
+    UNIQUE(plugin_id, version, command)
+);
+
+CREATE INDEX IF NOT EXISTS idx_plugin_requirements_command ON plugin_requirements(command);
+
+-- Platform specific archives for a single version; the generic archive stays in plugin_versions
+CREATE TABLE IF NOT EXISTS plugin_artifacts (
+    id SERIAL PRIMARY KEY,
+    plugin_id VARCHAR(255) NOT NULL REFERENCES plugins(id) ON DELETE CASCADE,
+    version VARCHAR(50) NOT NULL,
+    os VARCHAR(50) NOT NULL,
+    arch VARCHAR(50),
+    file_path VARCHAR(500) NOT NULL,
+    file_size BIGINT NOT NULL,
+    file_hash VARCHAR(64) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (plugin_id, version) REFERENCES plugin_versions(plugin_id, version) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_plugin_artifacts_unique
+    ON plugin_artifacts(plugin_id, version, os, COALESCE(arch, ''));
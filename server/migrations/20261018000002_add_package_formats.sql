@@ -0,0 +1,7 @@
+-- Keep the uploaded archive format and an optional tar.gz conversion for older clients
+
+ALTER TABLE plugin_versions ADD COLUMN IF NOT EXISTS file_format VARCHAR(10) NOT NULL DEFAULT 'tar.gz';
+ALTER TABLE plugin_versions ADD COLUMN IF NOT EXISTS normalized_file_path VARCHAR(500);
+
+ALTER TABLE plugin_artifacts ADD COLUMN IF NOT EXISTS file_format VARCHAR(10) NOT NULL DEFAULT 'tar.gz';
+ALTER TABLE plugin_artifacts ADD COLUMN IF NOT EXISTS normalized_file_path VARCHAR(500);
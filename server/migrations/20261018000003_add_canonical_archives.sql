@@ -0,0 +1,23 @@
+-- Canonical (reproducible) archives and per-file hashes
+-- normalized_file_path now always points at the canonical tar.gz built on upload
+
+ALTER TABLE plugin_versions ADD COLUMN IF NOT EXISTS canonical_hash VARCHAR(64);
+ALTER TABLE plugin_artifacts ADD COLUMN IF NOT EXISTS canonical_hash VARCHAR(64);
+
+CREATE INDEX IF NOT EXISTS idx_plugin_versions_canonical_hash ON plugin_versions(canonical_hash);
+
+-- One row per file of a version's canonical archive; artifact_id is set for platform archives
+CREATE TABLE IF NOT EXISTS plugin_files (
+    id SERIAL PRIMARY KEY,
+    plugin_id VARCHAR(255) NOT NULL,
+    version VARCHAR(50) NOT NULL,
+    artifact_id INTEGER REFERENCES plugin_artifacts(id) ON DELETE CASCADE,
+    path VARCHAR(1000) NOT NULL,
+    sha256 VARCHAR(64) NOT NULL,
+    size BIGINT NOT NULL,
+    is_executable BOOLEAN NOT NULL DEFAULT false,
+    FOREIGN KEY (plugin_id, version) REFERENCES plugin_versions(plugin_id, version) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_plugin_files_version ON plugin_files(plugin_id, version);
+CREATE INDEX IF NOT EXISTS idx_plugin_files_artifact_id ON plugin_files(artifact_id);
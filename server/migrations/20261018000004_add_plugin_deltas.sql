@@ -0,0 +1,17 @@
+-- Binary deltas between the canonical tars of two versions of the same plugin
+
+CREATE TABLE IF NOT EXISTS plugin_deltas (
+    id SERIAL PRIMARY KEY,
+    plugin_id VARCHAR(255) NOT NULL,
+    from_version VARCHAR(50) NOT NULL,
+    to_version VARCHAR(50) NOT NULL,
+    file_path VARCHAR(500) NOT NULL,
+    file_size BIGINT NOT NULL,
+    -- Hash and size of the uncompressed canonical tar the delta produces
+    result_hash VARCHAR(64) NOT NULL,
+    result_size BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (plugin_id, from_version) REFERENCES plugin_versions(plugin_id, version) ON DELETE CASCADE,
+    FOREIGN KEY (plugin_id, to_version) REFERENCES plugin_versions(plugin_id, version) ON DELETE CASCADE,
+    UNIQUE(plugin_id, from_version, to_version)
+);
@@ -0,0 +1,14 @@
+-- Permissions declared in info.json, one row per version that declares any
+
+CREATE TABLE IF NOT EXISTS plugin_permissions (
+    plugin_id VARCHAR(255) NOT NULL,
+    version VARCHAR(50) NOT NULL,
+    network BOOLEAN NOT NULL DEFAULT false,
+    root BOOLEAN NOT NULL DEFAULT false,
+    filesystem_read TEXT[] NOT NULL DEFAULT '{}',
+    filesystem_write TEXT[] NOT NULL DEFAULT '{}',
+    services TEXT[] NOT NULL DEFAULT '{}',
+    env_vars TEXT[] NOT NULL DEFAULT '{}',
+    PRIMARY KEY (plugin_id, version),
+    FOREIGN KEY (plugin_id, version) REFERENCES plugin_versions(plugin_id, version) ON DELETE CASCADE
+);
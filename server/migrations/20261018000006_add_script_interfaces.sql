@@ -0,0 +1,5 @@
+-- Typed script parameters, documented exit codes and output formats from info.json
+
+ALTER TABLE plugin_scripts ADD COLUMN IF NOT EXISTS parameters JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE plugin_scripts ADD COLUMN IF NOT EXISTS exit_codes JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE plugin_scripts ADD COLUMN IF NOT EXISTS output_formats TEXT[] NOT NULL DEFAULT '{}';
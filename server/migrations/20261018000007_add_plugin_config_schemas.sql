@@ -0,0 +1,9 @@
+-- Settings schema declared in info.json, one row per version that declares one
+
+CREATE TABLE IF NOT EXISTS plugin_config_schemas (
+    plugin_id VARCHAR(255) NOT NULL,
+    version VARCHAR(50) NOT NULL,
+    schema JSONB NOT NULL,
+    PRIMARY KEY (plugin_id, version),
+    FOREIGN KEY (plugin_id, version) REFERENCES plugin_versions(plugin_id, version) ON DELETE CASCADE
+);
@@ -0,0 +1,10 @@
+-- Lifecycle hook scripts declared in info.json
+
+CREATE TABLE IF NOT EXISTS plugin_hooks (
+    plugin_id VARCHAR(255) NOT NULL,
+    version VARCHAR(50) NOT NULL,
+    hook VARCHAR(20) NOT NULL,
+    script_file VARCHAR(255) NOT NULL,
+    PRIMARY KEY (plugin_id, version, hook),
+    FOREIGN KEY (plugin_id, version) REFERENCES plugin_versions(plugin_id, version) ON DELETE CASCADE
+);
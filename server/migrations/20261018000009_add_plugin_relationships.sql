@@ -0,0 +1,34 @@
+-- Optional dependencies, conflicts and virtual capabilities ("provides")
+
+-- Dependencies can now name a capability instead of a plugin, and are kept per version
+ALTER TABLE plugin_dependencies DROP CONSTRAINT IF EXISTS plugin_dependencies_dependency_id_fkey;
+ALTER TABLE plugin_dependencies ADD COLUMN IF NOT EXISTS version VARCHAR(50);
+ALTER TABLE plugin_dependencies ADD COLUMN IF NOT EXISTS optional BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE plugin_dependencies ADD COLUMN IF NOT EXISTS reason VARCHAR(255);
+
+-- Older rows were not tied to a version; attribute them to the current one
+UPDATE plugin_dependencies d SET version = p.current_version
+FROM plugins p WHERE d.plugin_id = p.id AND d.version IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_plugin_dependencies_version ON plugin_dependencies(plugin_id, version);
+
+CREATE TABLE IF NOT EXISTS plugin_conflicts (
+    id SERIAL PRIMARY KEY,
+    plugin_id VARCHAR(255) NOT NULL,
+    version VARCHAR(50) NOT NULL,
+    conflict_id VARCHAR(255) NOT NULL,
+    version_range VARCHAR(255),
+    FOREIGN KEY (plugin_id, version) REFERENCES plugin_versions(plugin_id, version) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_plugin_conflicts_version ON plugin_conflicts(plugin_id, version);
+
+CREATE TABLE IF NOT EXISTS plugin_provides (
+    plugin_id VARCHAR(255) NOT NULL,
+    version VARCHAR(50) NOT NULL,
+    capability VARCHAR(255) NOT NULL,
+    PRIMARY KEY (plugin_id, version, capability),
+    FOREIGN KEY (plugin_id, version) REFERENCES plugin_versions(plugin_id, version) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_plugin_provides_capability ON plugin_provides(capability);
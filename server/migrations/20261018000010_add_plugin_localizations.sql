@@ -0,0 +1,15 @@
+-- Localized names, descriptions and READMEs
+
+ALTER TABLE plugin_versions ADD COLUMN IF NOT EXISTS default_locale VARCHAR(35);
+ALTER TABLE plugin_versions ADD COLUMN IF NOT EXISTS readme TEXT;
+
+CREATE TABLE IF NOT EXISTS plugin_localizations (
+    plugin_id VARCHAR(255) NOT NULL,
+    version VARCHAR(50) NOT NULL,
+    locale VARCHAR(35) NOT NULL,
+    name VARCHAR(255),
+    description TEXT,
+    readme TEXT,
+    PRIMARY KEY (plugin_id, version, locale),
+    FOREIGN KEY (plugin_id, version) REFERENCES plugin_versions(plugin_id, version) ON DELETE CASCADE
+);
@@ -0,0 +1,7 @@
+-- Parsed SPDX license metadata. Rows uploaded before this keep the defaults until their next version.
+
+ALTER TABLE plugins ADD COLUMN IF NOT EXISTS license_spdx BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE plugins ADD COLUMN IF NOT EXISTS license_osi_approved BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE plugins ADD COLUMN IF NOT EXISTS license_ids TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS idx_plugins_license_ids ON plugins USING GIN (license_ids);
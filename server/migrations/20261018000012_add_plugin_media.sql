@@ -0,0 +1,21 @@
+-- Icons and screenshots, stored re-encoded with WebP and thumbnail variants
+
+CREATE TABLE IF NOT EXISTS plugin_media (
+    id SERIAL PRIMARY KEY,
+    plugin_id VARCHAR(255) NOT NULL,
+    version VARCHAR(50) NOT NULL,
+    kind VARCHAR(20) NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0,
+    -- png or jpg, the format of file_path and thumbnail_path
+    format VARCHAR(10) NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    file_path VARCHAR(500) NOT NULL,
+    webp_path VARCHAR(500) NOT NULL,
+    thumbnail_path VARCHAR(500) NOT NULL,
+    thumbnail_webp_path VARCHAR(500) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (plugin_id, version) REFERENCES plugin_versions(plugin_id, version) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_plugin_media_version ON plugin_media(plugin_id, version, kind);
@@ -0,0 +1,6 @@
+-- Funding and support links from info.json, refreshed with every version
+
+ALTER TABLE plugins ADD COLUMN IF NOT EXISTS issues_url VARCHAR(500);
+ALTER TABLE plugins ADD COLUMN IF NOT EXISTS docs_url VARCHAR(500);
+ALTER TABLE plugins ADD COLUMN IF NOT EXISTS support_email VARCHAR(255);
+ALTER TABLE plugins ADD COLUMN IF NOT EXISTS funding JSONB NOT NULL DEFAULT '[]';
@@ -0,0 +1,60 @@
+-- Full-text search document per plugin, weighted name > tags > description > author.
+-- Localized names and descriptions of the current version count like their originals.
+-- The 'simple' configuration is used because plugins are written in many languages.
+
+ALTER TABLE plugins ADD COLUMN IF NOT EXISTS search_vector tsvector NOT NULL DEFAULT ''::tsvector;
+
+CREATE OR REPLACE FUNCTION plugin_search_vector(
+    p_id VARCHAR, p_name VARCHAR, p_description TEXT, p_author VARCHAR, p_version VARCHAR
+) RETURNS tsvector AS $$
+    SELECT setweight(to_tsvector('simple', coalesce(p_name, '') || ' ' || coalesce(
+               (SELECT string_agg(l.name, ' ') FROM plugin_localizations l
+                WHERE l.plugin_id = p_id AND l.version = p_version), '')), 'A')
+        || setweight(to_tsvector('simple', coalesce(
+               (SELECT string_agg(t.tag, ' ') FROM plugin_tags t WHERE t.plugin_id = p_id), '')), 'B')
+        || setweight(to_tsvector('simple', coalesce(p_description, '') || ' ' || coalesce(
+               (SELECT string_agg(l.description, ' ') FROM plugin_localizations l
+                WHERE l.plugin_id = p_id AND l.version = p_version), '')), 'C')
+        || setweight(to_tsvector('simple', coalesce(p_author, '')), 'D')
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION update_plugin_search_vector()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.search_vector = plugin_search_vector(NEW.id, NEW.name, NEW.description, NEW.author, NEW.current_version);
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+-- Download counters and ratings update plugins constantly, so only recompute when a
+-- searched column is written. Tag and localization changes touch search_vector directly.
+CREATE TRIGGER update_plugins_search_vector
+    BEFORE INSERT OR UPDATE OF name, description, author, current_version, search_vector ON plugins
+    FOR EACH ROW EXECUTE FUNCTION update_plugin_search_vector();
+
+CREATE OR REPLACE FUNCTION touch_plugin_search_vector()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        UPDATE plugins SET search_vector = ''::tsvector WHERE id = OLD.plugin_id;
+        RETURN OLD;
+    END IF;
+    UPDATE plugins SET search_vector = ''::tsvector WHERE id = NEW.plugin_id;
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER update_plugin_tags_search_vector
+    AFTER INSERT OR UPDATE OR DELETE ON plugin_tags
+    FOR EACH ROW EXECUTE FUNCTION touch_plugin_search_vector();
+
+CREATE TRIGGER update_plugin_localizations_search_vector
+    AFTER INSERT OR UPDATE OR DELETE ON plugin_localizations
+    FOR EACH ROW EXECUTE FUNCTION touch_plugin_search_vector();
+
+-- Backfill without bumping updated_at
+ALTER TABLE plugins DISABLE TRIGGER update_plugins_updated_at;
+UPDATE plugins SET search_vector = ''::tsvector;
+ALTER TABLE plugins ENABLE TRIGGER update_plugins_updated_at;
+
+CREATE INDEX IF NOT EXISTS idx_plugins_search_vector ON plugins USING GIN (search_vector);
@@ -0,0 +1,42 @@
+-- CJK search. Postgres does not segment Chinese or Japanese, so the server stores character
+-- unigrams and bigrams of names and descriptions, computed at upload, and adds them to the
+-- search document. NULL means the plugin has not been indexed yet.
+
+ALTER TABLE plugins ADD COLUMN IF NOT EXISTS name_ngrams TEXT[];
+ALTER TABLE plugins ADD COLUMN IF NOT EXISTS description_ngrams TEXT[];
+
+CREATE OR REPLACE FUNCTION update_plugin_search_vector()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.search_vector = plugin_search_vector(NEW.id, NEW.name, NEW.description, NEW.author, NEW.current_version)
+        || setweight(array_to_tsvector(coalesce(NEW.name_ngrams, '{}')), 'A')
+        || setweight(array_to_tsvector(coalesce(NEW.description_ngrams, '{}')), 'C');
+    RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+DROP TRIGGER IF EXISTS update_plugins_search_vector ON plugins;
+CREATE TRIGGER update_plugins_search_vector
+    BEFORE INSERT OR UPDATE OF name, description, author, current_version, search_vector,
+        name_ngrams, description_ngrams ON plugins
+    FOR EACH ROW EXECUTE FUNCTION update_plugin_search_vector();
+
+-- Indexing existing plugins at startup must not make them look recently updated
+DROP TRIGGER IF EXISTS update_plugins_updated_at ON plugins;
+CREATE TRIGGER update_plugins_updated_at BEFORE UPDATE ON plugins
+    FOR EACH ROW
+    WHEN (OLD.name_ngrams IS NOT DISTINCT FROM NEW.name_ngrams
+          AND OLD.description_ngrams IS NOT DISTINCT FROM NEW.description_ngrams)
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Pinyin of every Chinese plugin name, original or localized, for search suggestions
+CREATE TABLE IF NOT EXISTS plugin_name_pinyin (
+    plugin_id VARCHAR(255) NOT NULL REFERENCES plugins(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    pinyin TEXT NOT NULL,
+    initials TEXT NOT NULL,
+    PRIMARY KEY (plugin_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_plugin_name_pinyin_pinyin ON plugin_name_pinyin(pinyin text_pattern_ops);
+CREATE INDEX IF NOT EXISTS idx_plugin_name_pinyin_initials ON plugin_name_pinyin(initials text_pattern_ops);
@@ -0,0 +1,5 @@
+-- Typo-tolerant matching on plugin names, IDs and tags. Fuzzy matching only runs as a
+-- fallback when full-text search finds little, with thresholds chosen by the server, so
+-- the similarity functions are used directly rather than the GUC-driven operators.
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
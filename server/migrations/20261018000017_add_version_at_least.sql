@@ -0,0 +1,15 @@
+-- Semantic version comparison for versions matching ^\d+\.\d+\.\d+(-[a-zA-Z0-9]+)?$,
+-- used to find plugins compatible with a given GeekTools version.
+
+CREATE OR REPLACE FUNCTION version_at_least(version TEXT, minimum TEXT) RETURNS boolean AS $$
+    SELECT CASE
+        WHEN string_to_array(split_part(version, '-', 1), '.')::int[]
+             <> string_to_array(split_part(minimum, '-', 1), '.')::int[]
+            THEN string_to_array(split_part(version, '-', 1), '.')::int[]
+                 > string_to_array(split_part(minimum, '-', 1), '.')::int[]
+        -- Same release: a release satisfies its pre-releases, not the other way round
+        WHEN position('-' in version) = 0 THEN true
+        WHEN position('-' in minimum) = 0 THEN false
+        ELSE split_part(version, '-', 2) >= split_part(minimum, '-', 2)
+    END
+$$ LANGUAGE sql IMMUTABLE;
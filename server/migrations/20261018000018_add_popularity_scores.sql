@@ -0,0 +1,29 @@
+-- Recent popularity. Downloads are counted per plugin and day, and the server periodically
+-- folds them into scores so that sorting by them stays an index scan:
+--   trending_score: daily downloads of the last 3 weeks, halving in weight every 3 days
+--   hot_score: downloads in the last 7 days
+
+CREATE TABLE IF NOT EXISTS plugin_daily_downloads (
+    plugin_id VARCHAR(255) NOT NULL REFERENCES plugins(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    downloads INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (plugin_id, day)
+);
+
+CREATE INDEX IF NOT EXISTS idx_plugin_daily_downloads_day ON plugin_daily_downloads(day);
+
+ALTER TABLE plugins ADD COLUMN IF NOT EXISTS trending_score DOUBLE PRECISION NOT NULL DEFAULT 0;
+ALTER TABLE plugins ADD COLUMN IF NOT EXISTS hot_score DOUBLE PRECISION NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_plugins_trending_score ON plugins(trending_score DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_plugins_hot_score ON plugins(hot_score DESC, id DESC);
+
+-- Refreshing scores must not make plugins look recently updated
+DROP TRIGGER IF EXISTS update_plugins_updated_at ON plugins;
+CREATE TRIGGER update_plugins_updated_at BEFORE UPDATE ON plugins
+    FOR EACH ROW
+    WHEN (OLD.name_ngrams IS NOT DISTINCT FROM NEW.name_ngrams
+          AND OLD.description_ngrams IS NOT DISTINCT FROM NEW.description_ngrams
+          AND OLD.trending_score = NEW.trending_score
+          AND OLD.hot_score = NEW.hot_score)
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,15 @@
+-- "You might also like" recommendations, recomputed periodically by the server so that a
+-- detail page only needs one indexed lookup. Each plugin keeps its best few matches.
+
+CREATE TABLE IF NOT EXISTS plugin_related (
+    plugin_id VARCHAR(255) NOT NULL REFERENCES plugins(id) ON DELETE CASCADE,
+    related_id VARCHAR(255) NOT NULL REFERENCES plugins(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    score DOUBLE PRECISION NOT NULL,
+    shared_tags TEXT[] NOT NULL DEFAULT '{}',
+    same_author BOOLEAN NOT NULL DEFAULT false,
+    shared_dependencies TEXT[] NOT NULL DEFAULT '{}',
+    PRIMARY KEY (plugin_id, related_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_plugin_related_position ON plugin_related(plugin_id, position);
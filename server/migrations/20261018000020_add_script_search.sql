@@ -0,0 +1,17 @@
+-- Search within scripts. Names, file names and descriptions are indexed along with the text
+-- of the script itself, which the server extracts from the package at upload. Like plugins,
+-- CJK n-grams are computed by the server; NULL ngrams means the script is not indexed yet.
+
+ALTER TABLE plugin_scripts ADD COLUMN IF NOT EXISTS content TEXT;
+ALTER TABLE plugin_scripts ADD COLUMN IF NOT EXISTS ngrams TEXT[];
+
+-- File names are split on punctuation so that `scripts/disk_analyzer.sh` is found by `disk`
+ALTER TABLE plugin_scripts ADD COLUMN IF NOT EXISTS search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', script_name || ' ' || regexp_replace(script_file, '[^[:alnum:]]+', ' ', 'g')), 'A')
+    || setweight(to_tsvector('simple', coalesce(description, '')), 'B')
+    || setweight(array_to_tsvector(coalesce(ngrams, '{}')), 'B')
+    || setweight(to_tsvector('simple', coalesce(content, '')), 'D')
+) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_plugin_scripts_search_vector ON plugin_scripts USING GIN (search_vector);
+CREATE INDEX IF NOT EXISTS idx_plugin_scripts_plugin_version ON plugin_scripts(plugin_id, version);
@@ -0,0 +1,21 @@
+-- A sample of searches and plugin listings, for finding what users look for and do not
+-- find. Nothing identifies who searched: there is no user, address or client information,
+-- and the ID only serves to attribute the first click on a result.
+
+CREATE TABLE IF NOT EXISTS search_queries (
+    id UUID PRIMARY KEY,
+    source VARCHAR(20) NOT NULL,
+    -- Lowercased with whitespace collapsed; empty when only filters were used
+    query VARCHAR(200) NOT NULL,
+    filters JSONB NOT NULL DEFAULT '{}',
+    result_count BIGINT NOT NULL,
+    -- IDs of the first page of results, to find the position of a click
+    result_ids TEXT[] NOT NULL DEFAULT '{}',
+    clicked_plugin_id VARCHAR(255),
+    clicked_position INTEGER,
+    clicked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_queries_created_at ON search_queries(created_at);
+CREATE INDEX IF NOT EXISTS idx_search_queries_query ON search_queries(query, created_at);
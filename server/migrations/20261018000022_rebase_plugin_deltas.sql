@@ -0,0 +1,6 @@
+-- Deltas now go between the archives as a full download serves them rather than between
+-- canonical tars, so result_hash is the file_hash of the target version. Existing deltas
+-- cannot be applied to what clients downloaded; they are dropped and clients fall back to
+-- full downloads until newer versions are published.
+
+DELETE FROM plugin_deltas;
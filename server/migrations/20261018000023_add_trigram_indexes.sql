@@ -0,0 +1,31 @@
+-- Trigram indexes for fuzzy matching. Queries now select candidates with the indexable `%`
+-- and `<%` operators, whose thresholds the server sets per transaction, and compute the
+-- similarity functions only to rank what those return.
+
+CREATE INDEX IF NOT EXISTS idx_plugins_name_trgm ON plugins USING GIN (lower(name) gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_plugins_id_trgm ON plugins USING GIN (id gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_plugin_tags_tag_trgm ON plugin_tags USING GIN (lower(tag) gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_plugin_localizations_name_trgm ON plugin_localizations USING GIN (lower(name) gin_trgm_ops);
+
+-- Words of active plugins' names, IDs, current localized names and tags, weighted by
+-- downloads, that misspelled searches are corrected to. Refreshed periodically by the server.
+CREATE MATERIALIZED VIEW IF NOT EXISTS search_vocabulary AS
+SELECT word, SUM(downloads)::bigint AS popularity FROM (
+    SELECT regexp_split_to_table(lower(p.name || ' ' || p.id), '[^a-z0-9]+') AS word, coalesce(p.downloads, 0) AS downloads
+    FROM plugins p WHERE p.status = 'active'
+    UNION ALL
+    SELECT regexp_split_to_table(lower(pl.name), '[^a-z0-9]+'), coalesce(p.downloads, 0)
+    FROM plugins p
+    JOIN plugin_localizations pl ON pl.plugin_id = p.id AND pl.version = p.current_version
+    WHERE p.status = 'active' AND pl.name IS NOT NULL
+    UNION ALL
+    SELECT regexp_split_to_table(lower(pt.tag), '[^a-z0-9]+'), coalesce(p.downloads, 0)
+    FROM plugins p JOIN plugin_tags pt ON pt.plugin_id = p.id
+    WHERE p.status = 'active'
+) words
+WHERE length(word) >= 3
+GROUP BY word;
+
+-- Unique so that it can be refreshed concurrently, without blocking searches
+CREATE UNIQUE INDEX IF NOT EXISTS idx_search_vocabulary_word ON search_vocabulary(word);
+CREATE INDEX IF NOT EXISTS idx_search_vocabulary_word_trgm ON search_vocabulary USING GIN (word gin_trgm_ops);
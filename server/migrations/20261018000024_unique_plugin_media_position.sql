@@ -0,0 +1,13 @@
+-- Number media positions within each kind and keep them unique, so concurrent
+-- uploads cannot end up sharing a slot
+
+UPDATE plugin_media m
+SET position = r.position
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY plugin_id, version, kind ORDER BY position, id) - 1 AS position
+    FROM plugin_media
+) r
+WHERE m.id = r.id AND m.position <> r.position;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_plugin_media_position
+    ON plugin_media(plugin_id, version, kind, position);
@@ -8,10 +8,13 @@ use validator::Validate;
 use crate::{
     handlers::{success_response, success_response_with_message, AppError, Result},
     middleware::auth::get_user_from_token,
-    models::{AdminPaginationQuery, ExecuteSqlRequest, UpdateUserEmailRequest, DeletePluginRequest, BanUserRequest, UnbanUserRequest, TogglePluginStatusRequest},
-    services::AppState,
+    models::{AdminPaginationQuery, ExecuteSqlRequest, UpdateUserEmailRequest, DeletePluginRequest, BanUserRequest, UnbanUserRequest, TogglePluginStatusRequest, SearchAnalyticsQuery},
+    services::{analytics::SEARCH_LOG_SAMPLE_RATE, AppState},
 };
 
+/// Period of search analytics reports unless `days` is given.
+const DEFAULT_ANALYTICS_DAYS: i32 = 30;
+
 // Admin authentication middleware helper
 async fn require_admin(headers: &HeaderMap, state: &AppState) -> Result<(i32, String)> {
     let user = get_user_from_token(headers, &state.auth_service).await?;
@@ -306,4 +309,69 @@ pub async fn toggle_plugin_status(
         serde_json::json!({}),
         &format!("插件{}成功", action),
     ))
-}
\ No newline at end of file
+}
+// Most searched queries
+pub async fn get_top_search_queries(
+    headers: HeaderMap,
+    Query(params): Query<SearchAnalyticsQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>> {
+    let (_admin_id, _admin_email) = require_admin(&headers, &state).await?;
+
+    params.validate()?;
+
+    let days = params.days.unwrap_or(DEFAULT_ANALYTICS_DAYS);
+    let queries = state
+        .search_analytics
+        .top_queries(days, params.limit.unwrap_or(50) as i64)
+        .await?;
+
+    Ok(success_response(serde_json::json!({
+        "days": days,
+        "sample_rate": SEARCH_LOG_SAMPLE_RATE,
+        "queries": queries
+    })))
+}
+
+// Queries that found no plugins
+pub async fn get_zero_result_queries(
+    headers: HeaderMap,
+    Query(params): Query<SearchAnalyticsQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>> {
+    let (_admin_id, _admin_email) = require_admin(&headers, &state).await?;
+
+    params.validate()?;
+
+    let days = params.days.unwrap_or(DEFAULT_ANALYTICS_DAYS);
+    let queries = state
+        .search_analytics
+        .zero_result_queries(days, params.limit.unwrap_or(50) as i64)
+        .await?;
+
+    Ok(success_response(serde_json::json!({
+        "days": days,
+        "sample_rate": SEARCH_LOG_SAMPLE_RATE,
+        "queries": queries
+    })))
+}
+
+// Search click-through rate
+pub async fn get_search_click_through(
+    headers: HeaderMap,
+    Query(params): Query<SearchAnalyticsQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>> {
+    let (_admin_id, _admin_email) = require_admin(&headers, &state).await?;
+
+    params.validate()?;
+
+    let days = params.days.unwrap_or(DEFAULT_ANALYTICS_DAYS);
+    let stats = state.search_analytics.click_through(days).await?;
+
+    Ok(success_response(serde_json::json!({
+        "days": days,
+        "sample_rate": SEARCH_LOG_SAMPLE_RATE,
+        "click_through": stats
+    })))
+}
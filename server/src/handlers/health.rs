@@ -3,6 +3,7 @@ use serde_json::json;
 
 use crate::{
     handlers::{success_response, Result},
+    models::PluginFilters,
     services::AppState,
 };
 
@@ -42,9 +43,10 @@ pub async fn metrics(
     State(state): State<AppState>,
 ) -> Result<Json<serde_json::Value>> {
     // Get some basic metrics
+    let all_plugins = state.plugin_service.resolve_filters(&PluginFilters::default()).await?;
     let total_plugins = state
         .plugin_service
-        .count_plugins(None, None)
+        .count_plugins(&all_plugins)
         .await?;
 
     let total_users = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM users")
@@ -3,13 +3,20 @@ pub mod plugins;
 pub mod search;
 pub mod health;
 pub mod admin;
+pub mod schema;
 
 use axum::{
-    http::StatusCode,
+    http::{header::ACCEPT_LANGUAGE, HeaderMap, StatusCode},
     response::{IntoResponse, Json},
 };
 use serde_json::json;
 
+use crate::{
+    models::{ManifestIssue, PageRequest},
+    services::plugin::UploadError,
+    utils::{i18n, pagination::Cursor},
+};
+
 pub type Result<T> = std::result::Result<T, AppError>;
 
 #[derive(Debug)]
@@ -21,6 +28,7 @@ pub enum AppError {
     Forbidden(String),
     Internal(String),
     ValidationError(String),
+    InvalidPlugin(Vec<ManifestIssue>),
 }
 
 impl IntoResponse for AppError {
@@ -36,12 +44,23 @@ impl IntoResponse for AppError {
             AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.clone()),
             AppError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.clone()),
             AppError::ValidationError(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
+            AppError::InvalidPlugin(_) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Plugin package failed validation".to_string(),
+            ),
         };
 
-        let body = Json(json!({
-            "success": false,
-            "error": error_message
-        }));
+        let body = match &self {
+            AppError::InvalidPlugin(errors) => Json(json!({
+                "success": false,
+                "error": error_message,
+                "errors": errors
+            })),
+            _ => Json(json!({
+                "success": false,
+                "error": error_message
+            })),
+        };
 
         (status, body).into_response()
     }
@@ -53,26 +72,69 @@ impl From<sqlx::Error> for AppError {
     }
 }
 
+impl From<UploadError> for AppError {
+    fn from(err: UploadError) -> Self {
+        match err {
+            UploadError::Invalid(errors) => AppError::InvalidPlugin(errors),
+            UploadError::Storage(msg) => AppError::Internal(msg),
+            UploadError::Database(err) => AppError::Database(err),
+        }
+    }
+}
+
 impl From<validator::ValidationErrors> for AppError {
     fn from(err: validator::ValidationErrors) -> Self {
-        let messages: Vec<String> = err
-            .field_errors()
-            .into_iter()
-            .map(|(field, errors)| {
-                let field_errors: Vec<String> = errors
-                    .into_iter()
+        let mut messages = Vec::new();
+        collect_validation_messages(&err, &mut messages);
+        AppError::ValidationError(messages.join("; "))
+    }
+}
+
+/// Messages of every failed validation, including those of nested structs and lists.
+fn collect_validation_messages(errors: &validator::ValidationErrors, messages: &mut Vec<String>) {
+    for (field, kind) in errors.errors() {
+        match kind {
+            validator::ValidationErrorsKind::Field(field_errors) => {
+                let field_errors: Vec<String> = field_errors
+                    .iter()
                     .map(|error| {
                         error.message.as_ref()
                             .map(|msg| msg.to_string())
                             .unwrap_or_else(|| format!("Invalid {}", field))
                     })
                     .collect();
-                field_errors.join(", ")
-            })
-            .collect();
-        
-        AppError::ValidationError(messages.join("; "))
+                messages.push(field_errors.join(", "));
+            }
+            validator::ValidationErrorsKind::Struct(inner) => collect_validation_messages(inner, messages),
+            validator::ValidationErrorsKind::List(items) => {
+                for inner in items.values() {
+                    collect_validation_messages(inner, messages);
+                }
+            }
+        }
+    }
+}
+
+/// The client's preferred languages from Accept-Language, most preferred first.
+pub fn accept_languages(headers: &HeaderMap) -> Vec<String> {
+    headers
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(i18n::parse_accept_language)
+        .unwrap_or_default()
+}
+
+/// The page of a listing to fetch: `cursor` when given, which must come from a listing
+/// with the same sort, otherwise the offset of `page`.
+pub fn page_request(cursor: Option<&str>, page: i32, limit: i32, sort: &str, order: &str) -> Result<PageRequest> {
+    let Some(token) = cursor.filter(|token| !token.is_empty()) else {
+        return Ok(PageRequest::Offset((page - 1) * limit));
+    };
+    let cursor = Cursor::decode(token).ok_or_else(|| AppError::BadRequest("Invalid cursor".to_string()))?;
+    if cursor.sort != sort || cursor.order != order {
+        return Err(AppError::BadRequest("Cursor belongs to a listing with a different sort".to_string()));
     }
+    Ok(PageRequest::Cursor(cursor))
 }
 
 pub fn success_response<T: serde::Serialize>(data: T) -> Json<serde_json::Value> {
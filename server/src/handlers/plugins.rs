@@ -1,6 +1,6 @@
 use axum::{
     extract::{Multipart, Path, Query, State},
-    http::header,
+    http::{header, HeaderMap, HeaderName, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -9,64 +9,90 @@ use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    handlers::{success_response, success_response_with_message, AppError, Result},
+    handlers::{accept_languages, page_request, success_response, success_response_with_message, AppError, Result},
     middleware::auth::Claims,
     models::{
-        CreateRatingRequest, PaginationInfo, PluginListResponse,
-        PluginSearchQuery,
+        CreateRatingRequest, PageRequest, PaginationInfo, PluginFilters, PluginListResponse,
+        PluginPlatform, PluginSearchQuery, SearchLog, UpdateCheckRequest,
     },
-    services::AppState,
+    services::{plugin::RELATED_PLUGINS, AppState},
+    utils::{media::MediaKind, package::PackageFormat, validation::validate_platform},
 };
 
 pub async fn list_plugins(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Query(query): Query<PluginSearchQuery>,
 ) -> Result<Json<serde_json::Value>> {
-    // Simplified version for debugging
     let page = query.page.unwrap_or(1).max(1);
-    let limit = query.limit.unwrap_or(20).min(100).max(1);
-    let offset = (page - 1) * limit;
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let sort = query.sort.as_deref().unwrap_or("downloads");
+    let order = query.order.as_deref().unwrap_or("desc");
+    let page_request = page_request(query.cursor.as_deref(), page, limit, sort, order)?;
+
+    let filters = PluginFilters {
+        search: query.search.clone(),
+        tags: query.tag.clone().into_iter().collect(),
+        os: query.os.clone(),
+        arch: query.arch.clone(),
+        requires: query.requires.clone(),
+        provides: query.provides.clone(),
+        license: query.license.clone(),
+        osi_approved: query.osi_approved,
+        ..Default::default()
+    };
 
-    // Try to get real plugins from database
-    let plugins = match state
+    let filter = state.plugin_service.resolve_filters(&filters).await?;
+    let result = state
         .plugin_service
         .search_plugins(
-            query.search.as_deref(),
-            query.tag.as_deref(),
-            query.sort.as_deref().unwrap_or("downloads"),
-            query.order.as_deref().unwrap_or("desc"),
+            &filter,
+            sort,
+            order,
             limit,
-            offset,
+            &page_request,
+            &accept_languages(&headers),
         )
-        .await {
-            Ok(plugins) => plugins,
-            Err(e) => {
-                tracing::error!("Failed to search plugins: {:?}", e);
-                vec![]
-            }
-        };
+        .await?;
 
-    let total = match state
+    let total = state
         .plugin_service
-        .count_plugins(query.search.as_deref(), query.tag.as_deref())
-        .await {
-            Ok(count) => count,
-            Err(e) => {
-                tracing::error!("Failed to count plugins: {:?}", e);
-                0i64
-            }
-        };
+        .count_plugins(&filter)
+        .await?;
+
+    // Only first pages, so that paging through results does not count as searching again
+    let search_id = match page_request {
+        PageRequest::Offset(0) => state.search_analytics.log_search(SearchLog {
+            source: "list",
+            query: query.search.clone().unwrap_or_default(),
+            filters: serde_json::json!({
+                "tag": query.tag,
+                "os": query.os,
+                "arch": query.arch,
+                "requires": query.requires,
+                "provides": query.provides,
+                "license": query.license,
+                "osi_approved": query.osi_approved,
+            }),
+            result_count: total,
+            result_ids: result.plugins.iter().map(|plugin| plugin.id.clone()).collect(),
+        }),
+        _ => None,
+    };
 
     let pagination = PaginationInfo {
-        page,
+        page: matches!(page_request, PageRequest::Offset(_)).then_some(page),
         limit,
         total,
-        pages: 0,
+        pages: ((total + limit as i64 - 1) / limit as i64) as i32,
+        next_cursor: result.next_cursor,
+        prev_cursor: result.prev_cursor,
     };
 
     let response = PluginListResponse {
-        plugins,
+        plugins: result.plugins,
         pagination,
+        search_id,
     };
 
     Ok(success_response(response))
@@ -74,17 +100,29 @@ pub async fn list_plugins(
 
 pub async fn get_plugin(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(plugin_id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
 ) -> Result<Json<serde_json::Value>> {
     let plugin = state
         .plugin_service
-        .get_plugin_detail(&plugin_id)
+        .get_plugin_detail(&plugin_id, &accept_languages(&headers))
         .await?
         .ok_or_else(|| AppError::NotFound("Plugin not found".to_string()))?;
 
+    record_search_click(&state, &params, &plugin_id);
+
     Ok(success_response(plugin))
 }
 
+/// Credit a detail view or download to the search it came from, when the client passes
+/// the `search_id` of that search.
+fn record_search_click(state: &AppState, params: &HashMap<String, String>, plugin_id: &str) {
+    if let Some(search_id) = params.get("search_id").and_then(|id| Uuid::parse_str(id).ok()) {
+        state.search_analytics.record_click(search_id, plugin_id.to_string());
+    }
+}
+
 pub async fn upload_plugin(
     State(state): State<AppState>,
     claims: Claims,
@@ -96,16 +134,6 @@ pub async fn upload_plugin(
         let name = field.name().unwrap_or("").to_string();
         
         if name == "plugin_file" {
-            let filename = field.file_name()
-                .ok_or_else(|| AppError::BadRequest("No filename provided".to_string()))?
-                .to_string();
-
-            if !filename.ends_with(".tar.gz") {
-                return Err(AppError::BadRequest(
-                    "File must be a .tar.gz archive".to_string(),
-                ));
-            }
-
             let data = field.bytes().await.map_err(|_| {
                 AppError::BadRequest("Failed to read file data".to_string())
             })?;
@@ -130,6 +158,38 @@ pub async fn upload_plugin(
     Err(AppError::BadRequest("No plugin file provided".to_string()))
 }
 
+// Run the full upload validation on a package without publishing it
+pub async fn validate_plugin(
+    State(state): State<AppState>,
+    _claims: Claims,
+    mut multipart: Multipart,
+) -> Result<Json<serde_json::Value>> {
+    while let Some(field) = multipart.next_field().await.map_err(|_| {
+        AppError::BadRequest("Invalid multipart data".to_string())
+    })? {
+        let name = field.name().unwrap_or("").to_string();
+
+        if name == "plugin_file" {
+            let data = field.bytes().await.map_err(|_| {
+                AppError::BadRequest("Failed to read file data".to_string())
+            })?;
+
+            if data.len() > 100 * 1024 * 1024 {
+                return Err(AppError::BadRequest("File too large".to_string()));
+            }
+
+            let report = state
+                .plugin_service
+                .validate_plugin(data.to_vec())
+                .await?;
+
+            return Ok(success_response(report));
+        }
+    }
+
+    Err(AppError::BadRequest("No plugin file provided".to_string()))
+}
+
 // Temporary upload endpoint without authentication for testing
 pub async fn upload_plugin_temp(
     State(state): State<AppState>,
@@ -141,16 +201,6 @@ pub async fn upload_plugin_temp(
         let name = field.name().unwrap_or("").to_string();
         
         if name == "plugin_file" {
-            let filename = field.file_name()
-                .ok_or_else(|| AppError::BadRequest("No filename provided".to_string()))?
-                .to_string();
-
-            if !filename.ends_with(".tar.gz") {
-                return Err(AppError::BadRequest(
-                    "File must be a .tar.gz archive".to_string(),
-                ));
-            }
-
             let data = field.bytes().await.map_err(|_| {
                 AppError::BadRequest("Failed to read file data".to_string())
             })?;
@@ -171,16 +221,204 @@ pub async fn upload_plugin_temp(
     Err(AppError::BadRequest("No plugin file provided".to_string()))
 }
 
+/// Only the author of a plugin, or an admin, may publish files for it.
+async fn require_plugin_author(state: &AppState, plugin_id: &str, claims: &Claims) -> Result<()> {
+    let author = state
+        .plugin_service
+        .get_plugin_author(plugin_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Plugin not found".to_string()))?;
+    if author == claims.username {
+        return Ok(());
+    }
+
+    let is_admin = state.admin_service.is_admin(claims.user_id).await.map_err(|e| {
+        AppError::Internal(format!("Failed to check admin status: {}", e))
+    })?;
+    if !is_admin {
+        return Err(AppError::Forbidden("Only the plugin author can do this".to_string()));
+    }
+    Ok(())
+}
+
+// Upload a platform specific archive for an existing version
+pub async fn upload_platform_artifact(
+    State(state): State<AppState>,
+    Path(plugin_id): Path<String>,
+    claims: Claims,
+    mut multipart: Multipart,
+) -> Result<Json<serde_json::Value>> {
+    require_plugin_author(&state, &plugin_id, &claims).await?;
+
+    let mut os = None;
+    let mut arch = None;
+    let mut data = None;
+
+    while let Some(field) = multipart.next_field().await.map_err(|_| {
+        AppError::BadRequest("Invalid multipart data".to_string())
+    })? {
+        let name = field.name().unwrap_or("").to_string();
+
+        match name.as_str() {
+            "os" | "arch" => {
+                let value = field.text().await.map_err(|_| {
+                    AppError::BadRequest(format!("Failed to read {} field", name))
+                })?;
+                let value = value.trim().to_lowercase();
+                if !value.is_empty() {
+                    if name == "os" { os = Some(value) } else { arch = Some(value) }
+                }
+            }
+            "plugin_file" => {
+                let bytes = field.bytes().await.map_err(|_| {
+                    AppError::BadRequest("Failed to read file data".to_string())
+                })?;
+
+                if bytes.len() > 100 * 1024 * 1024 {
+                    return Err(AppError::BadRequest("File too large".to_string()));
+                }
+
+                data = Some(bytes.to_vec());
+            }
+            _ => {}
+        }
+    }
+
+    let os = os.ok_or_else(|| AppError::BadRequest("No platform os provided".to_string()))?;
+    validate_platform(&os, arch.as_deref()).map_err(AppError::BadRequest)?;
+    let data = data.ok_or_else(|| AppError::BadRequest("No plugin file provided".to_string()))?;
+
+    let artifact = state
+        .plugin_service
+        .upload_platform_artifact(&plugin_id, &PluginPlatform { os, arch }, data)
+        .await?;
+
+    Ok(success_response_with_message(
+        artifact,
+        "Platform archive uploaded successfully",
+    ))
+}
+
+pub async fn upload_plugin_media(
+    State(state): State<AppState>,
+    Path(plugin_id): Path<String>,
+    claims: Claims,
+    mut multipart: Multipart,
+) -> Result<Json<serde_json::Value>> {
+    require_plugin_author(&state, &plugin_id, &claims).await?;
+
+    let mut kind = None;
+    let mut data = None;
+
+    while let Some(field) = multipart.next_field().await.map_err(|_| {
+        AppError::BadRequest("Invalid multipart data".to_string())
+    })? {
+        match field.name().unwrap_or("") {
+            "kind" => {
+                let value = field.text().await.map_err(|_| {
+                    AppError::BadRequest("Failed to read kind field".to_string())
+                })?;
+                kind = Some(MediaKind::parse(value.trim()).ok_or_else(|| {
+                    AppError::BadRequest("kind must be icon or screenshot".to_string())
+                })?);
+            }
+            "file" => {
+                // The route's body limit stops oversized images before they are buffered
+                let bytes = field.bytes().await.map_err(|e| match e.status() {
+                    StatusCode::PAYLOAD_TOO_LARGE => AppError::BadRequest("File too large".to_string()),
+                    _ => AppError::BadRequest("Failed to read file data".to_string()),
+                })?;
+                data = Some(bytes.to_vec());
+            }
+            _ => {}
+        }
+    }
+
+    let kind = kind.ok_or_else(|| AppError::BadRequest("No media kind provided".to_string()))?;
+    let data = data.ok_or_else(|| AppError::BadRequest("No image file provided".to_string()))?;
+
+    let image = state
+        .plugin_service
+        .add_plugin_media(&plugin_id, kind, data)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Plugin not found".to_string()))?;
+
+    Ok(success_response_with_message(image, "Image uploaded successfully"))
+}
+
+/// Image files never change under a URL, so clients and proxies may cache them for good.
+pub async fn get_plugin_media(
+    State(state): State<AppState>,
+    Path((plugin_id, media_id, variant)): Path<(String, i32, String)>,
+) -> Result<Response> {
+    let (file_path, content_type) = state
+        .plugin_service
+        .get_media_file(&plugin_id, media_id, &variant)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Image not found".to_string()))?;
+
+    let file_data = tokio::fs::read(&file_path).await.map_err(|_| {
+        AppError::NotFound("Image file not found".to_string())
+    })?;
+
+    let headers = [
+        (header::CONTENT_TYPE, content_type),
+        (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+        (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+    ];
+    Ok((headers, file_data).into_response())
+}
+
 pub async fn download_plugin(
     State(state): State<AppState>,
     Path(plugin_id): Path<String>,
     Query(params): Query<HashMap<String, String>>,
 ) -> Result<Response> {
-    let version = params.get("version").map(|s| s.as_str());
+    let version = params
+        .get("to")
+        .or_else(|| params.get("version"))
+        .map(|s| s.as_str());
+    let platform = params.get("os").map(|os| PluginPlatform {
+        os: os.to_lowercase(),
+        arch: params.get("arch").map(|arch| arch.to_lowercase()),
+    });
+    record_search_click(&state, &params, &plugin_id);
+
+    // Deltas only exist between generic archives; anything else falls through to a full download
+    if let (Some(from), None, None) = (params.get("from"), &platform, params.get("format")) {
+        if let Some(delta) = state.delta_service.find_delta(&plugin_id, from, version).await? {
+            if let Ok(file_data) = tokio::fs::read(&delta.file_path).await {
+                state
+                    .plugin_service
+                    .increment_download_count(&plugin_id, Some(&delta.to_version))
+                    .await?;
+
+                let filename = format!("{}-{}-to-{}.delta.zst", plugin_id, delta.from_version, delta.to_version);
+                let headers = [
+                    (header::CONTENT_TYPE, "application/zstd".to_string()),
+                    (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+                    (HeaderName::from_static("x-delta-from"), delta.from_version),
+                    (HeaderName::from_static("x-delta-to"), delta.to_version),
+                    (HeaderName::from_static("x-delta-base-sha256"), delta.base_hash),
+                    (HeaderName::from_static("x-delta-result-sha256"), delta.result_hash),
+                    (HeaderName::from_static("x-delta-result-size"), delta.result_size.to_string()),
+                ];
+                return Ok((headers, file_data).into_response());
+            }
+        }
+    }
+
+    // Older clients can ask for format=tar.gz to get uploads in other formats converted
+    let format = match params.get("format") {
+        Some(f) => Some(PackageFormat::from_extension(f).ok_or_else(|| {
+            AppError::BadRequest(format!("Unsupported package format: {}", f))
+        })?),
+        None => None,
+    };
     
-    let (file_path, filename) = state
+    let (file_path, filename, format) = state
         .plugin_service
-        .get_download_info(&plugin_id, version)
+        .get_download_info(&plugin_id, version, platform.as_ref(), format)
         .await?
         .ok_or_else(|| AppError::NotFound("Plugin version not found".to_string()))?;
 
@@ -196,7 +434,7 @@ pub async fn download_plugin(
     })?;
 
     let headers = [
-        (header::CONTENT_TYPE, "application/gzip"),
+        (header::CONTENT_TYPE, format.content_type()),
         (
             header::CONTENT_DISPOSITION,
             &format!("attachment; filename=\"{}\"", filename),
@@ -206,6 +444,75 @@ pub async fn download_plugin(
     Ok((headers, file_data).into_response())
 }
 
+pub async fn get_plugin_files(
+    State(state): State<AppState>,
+    Path(plugin_id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>> {
+    let version = params.get("version").map(|s| s.as_str());
+    let platform = params.get("os").map(|os| PluginPlatform {
+        os: os.to_lowercase(),
+        arch: params.get("arch").map(|arch| arch.to_lowercase()),
+    });
+
+    let files = state
+        .plugin_service
+        .get_plugin_files(&plugin_id, version, platform.as_ref())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Plugin version not found".to_string()))?;
+
+    Ok(success_response(files))
+}
+
+pub async fn get_plugin_diff(
+    State(state): State<AppState>,
+    Path(plugin_id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>> {
+    let (Some(from), Some(to)) = (params.get("from"), params.get("to")) else {
+        return Err(AppError::BadRequest("Both from and to versions are required".to_string()));
+    };
+
+    let diff = state
+        .plugin_service
+        .get_plugin_diff(&plugin_id, from, to)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to diff {} {} to {}: {:?}", plugin_id, from, to, e);
+            AppError::Internal("Failed to diff plugin versions".to_string())
+        })?
+        .ok_or_else(|| AppError::NotFound("Plugin version not found".to_string()))?;
+
+    Ok(success_response(diff))
+}
+
+pub async fn check_updates(
+    State(state): State<AppState>,
+    Json(payload): Json<UpdateCheckRequest>,
+) -> Result<Json<serde_json::Value>> {
+    payload.validate()?;
+
+    let updates = state.plugin_service.check_updates(&payload.plugins).await?;
+
+    Ok(success_response(updates))
+}
+
+pub async fn get_plugin_config(
+    State(state): State<AppState>,
+    Path(plugin_id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>> {
+    let version = params.get("version").map(|s| s.as_str());
+
+    let config = state
+        .plugin_service
+        .get_plugin_config(&plugin_id, version)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Plugin version not found".to_string()))?;
+
+    Ok(success_response(config))
+}
+
 pub async fn get_plugin_stats(
     State(state): State<AppState>,
     Path(plugin_id): Path<String>,
@@ -219,6 +526,30 @@ pub async fn get_plugin_stats(
     Ok(success_response(stats))
 }
 
+pub async fn get_related_plugins(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(plugin_id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>> {
+    let limit = params
+        .get("limit")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(RELATED_PLUGINS)
+        .clamp(1, RELATED_PLUGINS);
+
+    let related = state
+        .plugin_service
+        .get_related_plugins(&plugin_id, limit, &accept_languages(&headers))
+        .await?
+        .ok_or_else(|| AppError::NotFound("Plugin not found".to_string()))?;
+
+    Ok(success_response(serde_json::json!({
+        "plugin_id": plugin_id,
+        "related": related
+    })))
+}
+
 pub async fn get_plugin_ratings(
     State(state): State<AppState>,
     Path(plugin_id): Path<String>,
@@ -0,0 +1,23 @@
+use axum::{
+    extract::Path,
+    http::header,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    handlers::{AppError, Result},
+    utils::manifest::{self, CURRENT_MANIFEST_VERSION},
+};
+
+// Current info.json schema
+pub async fn info_schema() -> Result<Response> {
+    info_schema_version(Path(CURRENT_MANIFEST_VERSION)).await
+}
+
+// info.json schema for a specific manifest_version
+pub async fn info_schema_version(Path(version): Path<u32>) -> Result<Response> {
+    let schema = manifest::schema(version)
+        .ok_or_else(|| AppError::NotFound(format!("Unknown manifest version {}", version)))?;
+
+    Ok(([(header::CONTENT_TYPE, "application/schema+json")], schema).into_response())
+}
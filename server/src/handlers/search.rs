@@ -1,79 +1,138 @@
-use axum::{extract::{Query, State}, Json};
+use axum::{extract::{Query, State}, http::HeaderMap, Json};
 use serde_json::json;
 use std::collections::HashMap;
+use validator::Validate;
 
 use crate::{
-    handlers::{success_response, Result},
+    handlers::{accept_languages, page_request, success_response, AppError, Result},
+    models::{AdvancedSearchRequest, PageRequest, PaginationInfo, SearchLog, SearchScope},
     services::AppState,
 };
 
 pub async fn advanced_search(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(payload): Json<serde_json::Value>,
 ) -> Result<Json<serde_json::Value>> {
-    let query = payload.get("query").and_then(|v| v.as_str()).unwrap_or("");
-    let page = payload.get("pagination")
-        .and_then(|p| p.get("page"))
-        .and_then(|v| v.as_i64())
-        .unwrap_or(1) as i32;
-    let limit = payload.get("pagination")
-        .and_then(|p| p.get("limit"))
-        .and_then(|v| v.as_i64())
-        .unwrap_or(20) as i32;
+    let logged_filters = payload.get("filters").cloned().unwrap_or_default();
+    // Parsed here rather than by the extractor so that errors name the offending key
+    let request: AdvancedSearchRequest = serde_path_to_error::deserialize(payload)
+        .map_err(|e| AppError::BadRequest(format!("Invalid search request at {}: {}", e.path(), e.inner())))?;
+    request.validate()?;
 
-    let filters = payload.get("filters");
-    let tag = filters
-        .and_then(|f| f.get("tags"))
-        .and_then(|t| t.as_array())
-        .and_then(|arr| arr.first())
-        .and_then(|v| v.as_str());
+    let plugin_filters = request.plugin_filters();
+    let query = request.query.as_str();
+    let page = request.pagination.page;
+    let limit = request.pagination.limit;
+    let sort_field = request.sort.field.as_str();
+    let sort_order = request.sort.order.as_str();
+    let page_request = page_request(request.pagination.cursor.as_deref(), page, limit, sort_field, sort_order)?;
 
-    let sort_field = payload.get("sort")
-        .and_then(|s| s.get("field"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("downloads");
-    let sort_order = payload.get("sort")
-        .and_then(|s| s.get("order"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("desc");
-
-    let offset = (page - 1) * limit;
+    if request.scope == SearchScope::Scripts {
+        let PageRequest::Offset(offset) = page_request else {
+            return Err(AppError::BadRequest("Script search pages by page number, not cursor".to_string()));
+        };
+        if !request.facets.is_empty() {
+            return Err(AppError::BadRequest("Facets are only available when searching plugins".to_string()));
+        }
+        let (scripts, total) = state
+            .plugin_service
+            .search_scripts(query, &plugin_filters, limit, offset, &accept_languages(&headers))
+            .await?;
+        let search_id = match offset {
+            0 => state.search_analytics.log_search(SearchLog {
+                source: "scripts",
+                query: query.to_string(),
+                filters: logged_filters,
+                result_count: total,
+                result_ids: scripts.iter().map(|script| script.plugin.id.clone()).collect(),
+            }),
+            _ => None,
+        };
+        return Ok(success_response(json!({
+            "scripts": scripts,
+            "search_id": search_id,
+            "pagination": PaginationInfo {
+                page: Some(page),
+                limit,
+                total,
+                pages: ((total + limit as i64 - 1) / limit as i64) as i32,
+                next_cursor: None,
+                prev_cursor: None,
+            }
+        })));
+    }
 
-    let plugins = state
+    let filter = state.plugin_service.resolve_filters(&plugin_filters).await?;
+    let result = state
         .plugin_service
         .search_plugins(
-            if query.is_empty() { None } else { Some(query) },
-            tag,
+            &filter,
             sort_field,
             sort_order,
             limit,
-            offset,
+            &page_request,
+            &accept_languages(&headers),
         )
         .await?;
 
     let total = state
         .plugin_service
-        .count_plugins(
-            if query.is_empty() { None } else { Some(query) },
-            tag,
-        )
+        .count_plugins(&filter)
         .await?;
 
-    let response = json!({
-        "plugins": plugins,
-        "pagination": {
-            "page": page,
-            "limit": limit,
-            "total": total,
-            "pages": ((total as f64) / (limit as f64)).ceil() as i32
+    let facets = state
+        .plugin_service
+        .search_facets(&filter, &request.facets)
+        .await?;
+
+    // Decided by the full-text matches: a typo can fuzzy match plenty of plugins
+    let did_you_mean = if filter.is_fuzzy() {
+        state.plugin_service.did_you_mean(query).await?
+    } else {
+        None
+    };
+
+    // Only first pages, so that paging through results does not count as searching again
+    let search_id = match page_request {
+        PageRequest::Offset(0) => state.search_analytics.log_search(SearchLog {
+            source: "search",
+            query: query.to_string(),
+            filters: logged_filters,
+            result_count: total,
+            result_ids: result.plugins.iter().map(|plugin| plugin.id.clone()).collect(),
+        }),
+        _ => None,
+    };
+
+    let mut response = json!({
+        "plugins": result.plugins,
+        "search_id": search_id,
+        "did_you_mean": did_you_mean,
+        "pagination": PaginationInfo {
+            page: matches!(page_request, PageRequest::Offset(_)).then_some(page),
+            limit,
+            total,
+            pages: ((total + limit as i64 - 1) / limit as i64) as i32,
+            next_cursor: result.next_cursor,
+            prev_cursor: result.prev_cursor,
         }
     });
+    if !request.facets.is_empty() {
+        response["facets"] = json!(facets);
+    }
 
     Ok(success_response(response))
 }
 
+pub async fn license_stats(State(state): State<AppState>) -> Result<Json<serde_json::Value>> {
+    let stats = state.plugin_service.get_license_stats().await?;
+    Ok(success_response(stats))
+}
+
 pub async fn search_suggestions(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Query(params): Query<HashMap<String, String>>,
 ) -> Result<Json<serde_json::Value>> {
     let query = params.get("q").map(|s| s.as_str()).unwrap_or("");
@@ -86,7 +145,7 @@ pub async fn search_suggestions(
 
     let suggestions = state
         .plugin_service
-        .get_search_suggestions(query)
+        .get_search_suggestions(query, &accept_languages(&headers))
         .await?;
 
     Ok(success_response(json!({
@@ -25,7 +25,7 @@ use tower_http::{
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use handlers::{auth, plugins, search, health, admin};
+use handlers::{auth, plugins, search, health, admin, schema};
 use services::AppState;
 use utils::config::Config;
 
@@ -119,15 +119,32 @@ fn create_app(state: AppState) -> Router {
         .route("/plugins", get(plugins::list_plugins))
         .route("/plugins", post(plugins::upload_plugin))
         .route("/plugins/upload", post(plugins::upload_plugin_temp)) // Temporary endpoint without auth
+        .route("/plugins/validate", post(plugins::validate_plugin))
+        .route("/plugins/check-updates", post(plugins::check_updates))
         .route("/plugins/:id", get(plugins::get_plugin))
         .route("/plugins/:id/download", get(plugins::download_plugin))
+        .route("/plugins/:id/files", get(plugins::get_plugin_files))
+        .route("/plugins/:id/diff", get(plugins::get_plugin_diff))
+        .route("/plugins/:id/config", get(plugins::get_plugin_config))
+        .route("/plugins/:id/artifacts", post(plugins::upload_platform_artifact))
+        .route(
+            "/plugins/:id/media",
+            post(plugins::upload_plugin_media).layer(DefaultBodyLimit::max(utils::media::MAX_UPLOAD_BODY)),
+        )
+        .route("/plugins/:id/media/:media_id/:variant", get(plugins::get_plugin_media))
         .route("/plugins/:id/stats", get(plugins::get_plugin_stats))
+        .route("/plugins/:id/related", get(plugins::get_related_plugins))
         .route("/plugins/:id/ratings", get(plugins::get_plugin_ratings))
         .route("/plugins/:id/ratings", post(plugins::create_rating))
         
         // Search routes
         .route("/search", post(search::advanced_search))
         .route("/search/suggestions", get(search::search_suggestions))
+        .route("/search/licenses", get(search::license_stats))
+        
+        // Manifest schema
+        .route("/schema/info.json", get(schema::info_schema))
+        .route("/schema/info/:version", get(schema::info_schema_version))
         
         // Health check
         .route("/health", get(health::health_check))
@@ -145,6 +162,9 @@ fn create_app(state: AppState) -> Router {
         .route("/admin/sql/execute", post(admin::execute_sql))
         .route("/admin/login-activities", get(admin::get_user_login_activities))
         .route("/admin/recent-logins", get(admin::get_recent_logins))
+        .route("/admin/search/top-queries", get(admin::get_top_search_queries))
+        .route("/admin/search/zero-results", get(admin::get_zero_result_queries))
+        .route("/admin/search/click-through", get(admin::get_search_click_through))
         
         .with_state(state);
 
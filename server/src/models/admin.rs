@@ -128,6 +128,16 @@ pub struct AdminPaginationQuery {
     pub limit: Option<i32>,
 }
 
+/// Period and size of a search analytics report.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SearchAnalyticsQuery {
+    /// Days back from now, 30 by default.
+    #[validate(range(min = 1, max = 365))]
+    pub days: Option<i32>,
+    #[validate(range(min = 1, max = 200))]
+    pub limit: Option<i32>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct DeletePluginRequest {
     pub plugin_id: String,
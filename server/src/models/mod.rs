@@ -3,9 +3,11 @@ pub mod user;
 pub mod rating;
 pub mod auth;
 pub mod admin;
+pub mod search;
 
 pub use plugin::*;
 pub use user::*;
 pub use rating::*;
 pub use auth::*;
-pub use admin::*;
\ No newline at end of file
+pub use admin::*;
+pub use search::*;
\ No newline at end of file
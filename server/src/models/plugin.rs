@@ -76,22 +76,242 @@ pub struct PluginDependency {
 // Request/Response DTOs
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreatePluginRequest {
+    #[serde(default = "default_manifest_version")]
+    pub manifest_version: u32,
     #[validate(length(min = 3, max = 50))]
     pub id: String,
     #[validate(length(min = 1, max = 255))]
     pub name: String,
     pub description: Option<String>,
+    /// Language of `name`, `description` and README.md.
+    pub default_locale: Option<String>,
+    #[serde(default)]
+    pub name_i18n: std::collections::BTreeMap<String, String>,
+    #[serde(default)]
+    pub description_i18n: std::collections::BTreeMap<String, String>,
     #[validate(length(min = 1, max = 255))]
     pub author: String,
     pub version: String,
     pub min_geektools_version: Option<String>,
     pub homepage_url: Option<String>,
     pub repository_url: Option<String>,
+    pub issues_url: Option<String>,
+    pub docs_url: Option<String>,
+    #[validate(email, length(max = 255))]
+    pub support_email: Option<String>,
+    #[serde(default)]
+    pub funding: Vec<FundingLink>,
     pub license: Option<String>,
     pub tags: Vec<String>,
-    #[validate(length(min = 1))]
+    #[validate(length(min = 1), nested)]
     pub scripts: Vec<PluginScriptInfo>,
     pub dependencies: Vec<PluginDependencyInfo>,
+    #[serde(default)]
+    pub conflicts: Vec<PluginConflict>,
+    /// Virtual capabilities (e.g. `notifier`) other plugins can depend on.
+    #[serde(default)]
+    pub provides: Vec<String>,
+    #[serde(default)]
+    pub platforms: Vec<PluginPlatform>,
+    #[serde(default)]
+    pub requires: Vec<String>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub permissions: PluginPermissions,
+    pub config: Option<PluginConfigSchema>,
+    #[serde(default)]
+    pub hooks: PluginHooks,
+}
+
+/// Where users can sponsor the author. Known platforms must link to their own site.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingLink {
+    #[serde(rename = "type")]
+    pub funding_type: FundingType,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FundingType {
+    GithubSponsors,
+    OpenCollective,
+    Patreon,
+    KoFi,
+    Liberapay,
+    Custom,
+}
+
+impl FundingType {
+    /// The site a link of this type must point at, with or without `www.`.
+    pub fn host(&self) -> Option<&'static str> {
+        match self {
+            Self::GithubSponsors => Some("github.com"),
+            Self::OpenCollective => Some("opencollective.com"),
+            Self::Patreon => Some("patreon.com"),
+            Self::KoFi => Some("ko-fi.com"),
+            Self::Liberapay => Some("liberapay.com"),
+            Self::Custom => None,
+        }
+    }
+}
+
+/// Scripts the client runs around install, uninstall and upgrade. Each points at a file in the
+/// package, resolved like `PluginScriptInfo::file`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginHooks {
+    pub pre_install: Option<String>,
+    pub post_install: Option<String>,
+    pub pre_uninstall: Option<String>,
+    pub post_upgrade: Option<String>,
+}
+
+impl PluginHooks {
+    pub const NAMES: [&'static str; 4] = ["pre_install", "post_install", "pre_uninstall", "post_upgrade"];
+
+    /// The declared hooks as (hook name, script file) pairs.
+    pub fn declared(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        Self::NAMES
+            .into_iter()
+            .zip([&self.pre_install, &self.post_install, &self.pre_uninstall, &self.post_upgrade])
+            .filter_map(|(name, file)| file.as_deref().map(|file| (name, file)))
+    }
+
+    pub fn set(&mut self, name: &str, file: String) {
+        let slot = match name {
+            "pre_install" => &mut self.pre_install,
+            "post_install" => &mut self.post_install,
+            "pre_uninstall" => &mut self.pre_uninstall,
+            "post_upgrade" => &mut self.post_upgrade,
+            _ => return,
+        };
+        *slot = Some(file);
+    }
+}
+
+/// What a plugin version declares it will touch on the host. Everything defaults to nothing.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Validate)]
+pub struct PluginPermissions {
+    #[serde(default)]
+    pub network: bool,
+    #[serde(default)]
+    pub root: bool,
+    #[serde(default)]
+    #[validate(length(max = 50))]
+    pub filesystem: Vec<FilesystemPermission>,
+    #[serde(default)]
+    #[validate(length(max = 50))]
+    pub services: Vec<String>,
+    #[serde(default)]
+    #[validate(length(max = 50))]
+    pub env: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilesystemPermission {
+    pub path: String,
+    #[serde(default)]
+    pub write: bool,
+}
+
+impl PluginPermissions {
+    /// Permissions requested here that `previous` did not already grant.
+    /// Write access to a path that was only readable counts as new.
+    pub fn added_since(&self, previous: &PluginPermissions) -> PluginPermissions {
+        PluginPermissions {
+            network: self.network && !previous.network,
+            root: self.root && !previous.root,
+            filesystem: self
+                .filesystem
+                .iter()
+                .filter(|fs| {
+                    !previous
+                        .filesystem
+                        .iter()
+                        .any(|old| old.path == fs.path && (old.write || !fs.write))
+                })
+                .cloned()
+                .collect(),
+            services: self.services.iter().filter(|s| !previous.services.contains(s)).cloned().collect(),
+            env: self.env.iter().filter(|e| !previous.env.contains(e)).cloned().collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == PluginPermissions::default()
+    }
+}
+
+/// The settings a plugin reads, as a flat JSON Schema subset:
+/// `{"type": "object", "properties": {...}, "required": [...]}`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PluginConfigSchema {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub schema_type: Option<String>,
+    #[serde(default)]
+    pub properties: std::collections::BTreeMap<String, ConfigProperty>,
+    #[serde(default)]
+    pub required: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigProperty {
+    #[serde(rename = "type")]
+    pub value_type: ConfigValueType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+    #[serde(default, rename = "enum", skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<serde_json::Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<serde_json::Number>,
+    /// Element schema, required for arrays.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<ConfigItems>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigItems {
+    #[serde(rename = "type")]
+    pub value_type: ConfigValueType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigValueType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Array,
+}
+
+/// A config change between two versions that can break an existing installation's settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigBreakingChange {
+    pub key: String,
+    /// `removed`, `type_changed` or `newly_required`
+    pub change: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PluginConfigResponse {
+    pub plugin_id: String,
+    pub version: String,
+    pub schema: Option<PluginConfigSchema>,
+    pub defaults: serde_json::Map<String, serde_json::Value>,
+}
+
+/// An OS/architecture pair a plugin runs on. A missing `arch` means any architecture.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginPlatform {
+    pub os: String,
+    pub arch: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -102,18 +322,80 @@ pub struct PluginScriptInfo {
     pub file: String,
     pub description: Option<String>,
     pub executable: bool,
+    #[serde(default)]
+    #[validate(length(max = 50), nested)]
+    pub parameters: Vec<ScriptParameter>,
+    #[serde(default)]
+    #[validate(length(max = 50), nested)]
+    pub exit_codes: Vec<ScriptExitCode>,
+    #[serde(default)]
+    pub output_formats: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParameterType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Path,
+}
+
+/// One argument a script accepts, enough for a client to render a run form.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ScriptParameter {
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: ParameterType,
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
+    /// Allowed values; empty means any value of the type.
+    #[serde(default, rename = "enum")]
+    #[validate(length(max = 100))]
+    pub choices: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub required: bool,
+    #[validate(length(max = 1000))]
+    pub help: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ScriptExitCode {
+    pub code: i32,
+    #[validate(length(min = 1, max = 255))]
+    pub description: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PluginDependencyInfo {
+    /// A plugin ID, or a capability some plugin `provides`.
     pub id: String,
     pub min_version: Option<String>,
+    /// Optional dependencies enhance the plugin but are not needed to install it.
+    #[serde(default)]
+    pub optional: bool,
+    /// Why an optional dependency is worth installing; required for optional dependencies.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// A plugin that cannot be installed alongside this one. A missing range means any version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginConflict {
+    pub id: String,
+    #[serde(default)]
+    pub versions: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PluginListResponse {
     pub plugins: Vec<PluginSummary>,
     pub pagination: PaginationInfo,
+    /// Pass as `search_id` when opening or downloading one of the plugins, when present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_id: Option<uuid::Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -127,10 +409,50 @@ pub struct PluginSummary {
     #[serde(serialize_with = "serialize_bigdecimal", deserialize_with = "deserialize_bigdecimal")]
     pub rating: BigDecimal,
     pub tags: Vec<String>,
+    pub platforms: Vec<PluginPlatform>,
+    pub requires: Vec<String>,
+    pub icon: Option<PluginImage>,
+    /// Deprecated plugins only show up when a search asks for them.
+    #[serde(default)]
+    pub deprecated: bool,
+    /// Only present in search results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlights: Option<PluginHighlights>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Where a search matched, HTML-escaped with matching words wrapped in `<mark>`.
+/// A field is `None` when it did not match.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginHighlights {
+    pub name: Option<String>,
+    /// A short fragment around the first match.
+    pub description: Option<String>,
+}
+
+/// A plugin recommended alongside another, with what they have in common.
+#[derive(Debug, Serialize)]
+pub struct RelatedPlugin {
+    #[serde(flatten)]
+    pub plugin: PluginSummary,
+    pub score: f64,
+    pub shared_tags: Vec<String>,
+    pub same_author: bool,
+    pub shared_dependencies: Vec<String>,
+}
+
+/// An icon or screenshot. Every URL is immutable and cached for a year.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginImage {
+    pub url: String,
+    pub webp_url: String,
+    pub thumbnail_url: String,
+    pub thumbnail_webp_url: String,
+    pub width: i32,
+    pub height: i32,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PluginDetailResponse {
     pub id: String,
@@ -145,10 +467,30 @@ pub struct PluginDetailResponse {
     pub min_geektools_version: Option<String>,
     pub homepage_url: Option<String>,
     pub repository_url: Option<String>,
+    pub issues_url: Option<String>,
+    pub docs_url: Option<String>,
+    pub support_email: Option<String>,
+    pub funding: Vec<FundingLink>,
     pub license: Option<String>,
+    /// False for custom licenses explained by a bundled LICENSE file.
+    pub license_spdx: bool,
+    pub license_osi_approved: bool,
     pub versions: Vec<PluginVersionInfo>,
     pub scripts: Vec<PluginScriptInfo>,
     pub dependencies: Vec<PluginDependencyInfo>,
+    pub conflicts: Vec<PluginConflict>,
+    pub provides: Vec<String>,
+    pub platforms: Vec<PluginPlatform>,
+    pub requires: Vec<String>,
+    pub permissions: PluginPermissions,
+    pub config: Option<PluginConfigSchema>,
+    pub hooks: PluginHooks,
+    /// Locale `name`, `description` and `readme` are in, when known.
+    pub locale: Option<String>,
+    pub available_locales: Vec<String>,
+    pub readme: Option<String>,
+    pub icon: Option<PluginImage>,
+    pub screenshots: Vec<PluginImage>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -158,29 +500,177 @@ pub struct PluginVersionInfo {
     pub version: String,
     pub changelog: Option<String>,
     pub file_size: i64,
+    pub file_format: String,
+    pub canonical_hash: Option<String>,
     pub created_at: DateTime<Utc>,
     pub downloads: i32,
     pub is_stable: bool,
+    /// Breaking config changes compared to the previous version in this listing.
+    pub config_breaking_changes: Vec<ConfigBreakingChange>,
+    pub artifacts: Vec<PluginArtifactInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PluginArtifactInfo {
+    pub os: String,
+    pub arch: Option<String>,
+    pub file_format: String,
+    pub file_size: i64,
+    pub file_hash: String,
+    pub canonical_hash: Option<String>,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PaginationInfo {
-    pub page: i32,
+    /// `None` when paging by cursor.
+    pub page: Option<i32>,
     pub limit: i32,
     pub total: i64,
     pub pages: i32,
+    /// Pass as `cursor` to fetch the following page, absent on the last one.
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct PluginSearchQuery {
     pub page: Option<i32>,
     pub limit: Option<i32>,
+    /// Takes precedence over `page`.
+    pub cursor: Option<String>,
     pub search: Option<String>,
     pub tag: Option<String>,
+    pub os: Option<String>,
+    pub arch: Option<String>,
+    pub requires: Option<String>,
+    pub provides: Option<String>,
+    pub license: Option<String>,
+    pub osi_approved: Option<bool>,
     pub sort: Option<String>,
     pub order: Option<String>,
 }
 
+/// Filters shared by plugin listing, search and counting.
+#[derive(Debug, Default, Clone)]
+pub struct PluginFilters {
+    pub search: Option<String>,
+    pub tags: Vec<String>,
+    /// Require every tag rather than any of them.
+    pub all_tags: bool,
+    pub author: Option<String>,
+    pub os: Option<String>,
+    pub arch: Option<String>,
+    pub requires: Option<String>,
+    pub provides: Option<String>,
+    /// A license ID, matching its variants too: `GPL` finds `GPL-2.0-only` and `GPL-3.0-or-later`.
+    pub license: Option<String>,
+    pub osi_approved: Option<bool>,
+    pub min_rating: Option<f64>,
+    pub min_downloads: Option<i64>,
+    pub max_downloads: Option<i64>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+    /// A GeekTools version the plugin's current version must support.
+    pub geektools_version: Option<String>,
+    pub has_executable_scripts: Option<bool>,
+    pub include_deprecated: bool,
+}
+
+/// How many active plugins use a license, alone or as part of an expression.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LicenseCount {
+    pub license: String,
+    pub osi_approved: bool,
+    pub plugins: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LicenseStatsResponse {
+    pub licenses: Vec<LicenseCount>,
+    /// Plugins with a custom, non-SPDX license.
+    pub custom: i64,
+    pub unlicensed: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginFileInfo {
+    pub path: String,
+    pub sha256: String,
+    pub size: i64,
+    pub executable: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PluginFilesResponse {
+    pub plugin_id: String,
+    pub version: String,
+    pub platform: Option<PluginPlatform>,
+    pub canonical_hash: Option<String>,
+    pub files: Vec<PluginFileInfo>,
+}
+
+/// A file that differs between two versions. Modes are octal strings such as "755".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginFileDiff {
+    pub path: String,
+    pub old_size: Option<i64>,
+    pub new_size: Option<i64>,
+    pub old_mode: Option<String>,
+    pub new_mode: Option<String>,
+    pub content_changed: bool,
+    pub binary: bool,
+    /// Unified diff of text files; absent for binary files or when over the size caps.
+    pub diff: Option<String>,
+    pub diff_truncated: bool,
+}
+
+/// A changed info.json value, located by a JSON pointer. `None` means the field is absent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestChange {
+    pub pointer: String,
+    pub old_value: Option<serde_json::Value>,
+    pub new_value: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PluginDiffResponse {
+    pub plugin_id: String,
+    pub from_version: String,
+    pub to_version: String,
+    pub added: Vec<PluginFileDiff>,
+    pub removed: Vec<PluginFileDiff>,
+    pub modified: Vec<PluginFileDiff>,
+    pub manifest_changes: Vec<ManifestChange>,
+}
+
+/// A single manifest problem, located by a JSON pointer into info.json.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestIssue {
+    pub pointer: String,
+    pub message: String,
+}
+
+impl ManifestIssue {
+    pub fn new(pointer: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            pointer: pointer.into(),
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub manifest_version: Option<u32>,
+    pub plugin_id: Option<String>,
+    pub version: Option<String>,
+    pub errors: Vec<ManifestIssue>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UploadResponse {
     pub plugin_id: String,
@@ -188,6 +678,30 @@ pub struct UploadResponse {
     pub upload_id: String,
 }
 
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateCheckRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub plugins: Vec<InstalledPlugin>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledPlugin {
+    pub id: String,
+    pub version: String,
+}
+
+/// An available update, with the permissions the client should ask about before upgrading.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PluginUpdateInfo {
+    pub plugin_id: String,
+    pub installed_version: String,
+    pub latest_version: String,
+    pub new_permissions: PluginPermissions,
+    pub requires_consent: bool,
+    /// Installed plugins the latest version declares a conflict with.
+    pub conflicts_with: Vec<InstalledPlugin>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PluginStatsResponse {
     pub total_downloads: i32,
@@ -203,6 +717,10 @@ pub struct DownloadTrendItem {
     pub downloads: i32,
 }
 
+fn default_manifest_version() -> u32 {
+    1
+}
+
 impl Default for PluginStatus {
     fn default() -> Self {
         PluginStatus::Active
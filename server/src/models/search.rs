@@ -0,0 +1,290 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+use crate::models::{PluginFilters, PluginSummary};
+use crate::utils::{pagination::Cursor, validation};
+
+/// Body of `POST /search`. Every part is optional.
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(default)]
+pub struct AdvancedSearchRequest {
+    #[validate(length(max = 200))]
+    pub query: String,
+    /// Whether to find plugins or the scripts inside them.
+    pub scope: SearchScope,
+    #[validate(nested)]
+    pub filters: SearchFilters,
+    pub sort: SearchSort,
+    #[validate(nested)]
+    pub pagination: SearchPagination,
+    /// Counts to return alongside the results, for refining the search.
+    pub facets: Vec<SearchFacet>,
+}
+
+/// Unknown keys are rejected, so that a misspelled filter cannot silently widen a search.
+#[derive(Debug, Default, Deserialize, Validate)]
+#[serde(default, deny_unknown_fields)]
+#[validate(schema(function = "validate_search_filters"))]
+pub struct SearchFilters {
+    #[validate(length(max = 10))]
+    pub tags: Vec<String>,
+    /// Whether plugins need `any` of the tags, the default, or `all` of them.
+    pub tags_match: TagMatch,
+    #[validate(length(min = 1, max = 255))]
+    pub author: Option<String>,
+    pub license: Option<String>,
+    pub osi_approved: Option<bool>,
+    pub os: Option<String>,
+    pub arch: Option<String>,
+    pub requires: Option<String>,
+    pub provides: Option<String>,
+    #[validate(range(min = 0.0, max = 5.0))]
+    pub min_rating: Option<f64>,
+    #[validate(range(min = 0))]
+    pub min_downloads: Option<i64>,
+    #[validate(range(min = 0))]
+    pub max_downloads: Option<i64>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+    /// Only plugins whose current version runs on this GeekTools version.
+    pub geektools_version: Option<String>,
+    pub has_executable_scripts: Option<bool>,
+    /// Defaults to `true`; `false` includes deprecated plugins.
+    pub exclude_deprecated: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchScope {
+    #[default]
+    Plugins,
+    /// Scripts of current versions, matched by name, file name, description and source.
+    /// Filters still apply to the plugins shipping them.
+    Scripts,
+}
+
+/// A script matching a search, with the plugin that ships it.
+#[derive(Debug, Serialize)]
+pub struct ScriptSearchResult {
+    pub name: String,
+    pub file: String,
+    pub description: Option<String>,
+    pub executable: bool,
+    pub highlights: ScriptHighlights,
+    pub plugin: PluginSummary,
+}
+
+/// Where a script matched, marked up like `PluginHighlights`.
+#[derive(Debug, Serialize)]
+pub struct ScriptHighlights {
+    pub name: Option<String>,
+    pub file: Option<String>,
+    pub description: Option<String>,
+    /// The first lines of the source that match, with some context.
+    pub snippet: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    #[default]
+    Any,
+    All,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchFacet {
+    Tags,
+    License,
+    Author,
+    /// Operating systems. Plugins that declare no platforms count towards every one.
+    Platform,
+    Rating,
+}
+
+/// Plugins matching a search per facet value, most common first. Only requested facets
+/// are present.
+#[derive(Debug, Default, Serialize)]
+pub struct SearchFacets {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<FacetCount>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<Vec<FacetCount>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<Vec<FacetCount>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<Vec<FacetCount>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rating: Option<Vec<RatingBucket>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: i64,
+}
+
+/// Plugins rated at least `min_rating`, matching the `min_rating` filter.
+#[derive(Debug, Serialize)]
+pub struct RatingBucket {
+    pub min_rating: i32,
+    pub count: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SearchSort {
+    pub field: String,
+    pub order: String,
+}
+
+impl Default for SearchSort {
+    fn default() -> Self {
+        Self {
+            field: "downloads".to_string(),
+            order: "desc".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(default)]
+pub struct SearchPagination {
+    #[validate(range(min = 1))]
+    pub page: i32,
+    #[validate(range(min = 1, max = 100))]
+    pub limit: i32,
+    /// A `next_cursor` or `prev_cursor` from an earlier response. Takes precedence over `page`.
+    pub cursor: Option<String>,
+}
+
+impl Default for SearchPagination {
+    fn default() -> Self {
+        Self { page: 1, limit: 20, cursor: None }
+    }
+}
+
+/// A search to record for analytics.
+#[derive(Debug)]
+pub struct SearchLog {
+    /// `list`, `search` or `scripts`.
+    pub source: &'static str,
+    pub query: String,
+    pub filters: serde_json::Value,
+    pub result_count: i64,
+    /// Plugins on the first page of results, in order.
+    pub result_ids: Vec<String>,
+}
+
+/// How often a query was searched and how its results fared. Counts are of the sample.
+#[derive(Debug, Serialize)]
+pub struct QueryStats {
+    pub query: String,
+    pub searches: i64,
+    pub average_results: f64,
+    pub clicks: i64,
+    pub click_through_rate: f64,
+}
+
+/// A query that found nothing, likely a plugin users want that does not exist.
+#[derive(Debug, Serialize)]
+pub struct ZeroResultQuery {
+    pub query: String,
+    pub searches: i64,
+    pub last_searched_at: DateTime<Utc>,
+}
+
+/// Share of searches with results that led to a detail view or download of one of them.
+#[derive(Debug, Serialize)]
+pub struct ClickThroughStats {
+    pub searches: i64,
+    pub clicks: i64,
+    pub click_through_rate: f64,
+    /// Average 1-based position of the clicked result; lower means better ranking.
+    pub average_click_position: Option<f64>,
+    pub daily: Vec<DailyClickThrough>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyClickThrough {
+    pub day: chrono::NaiveDate,
+    pub searches: i64,
+    pub clicks: i64,
+    pub click_through_rate: f64,
+}
+
+/// Which page of a plugin listing to fetch.
+#[derive(Debug, Clone)]
+pub enum PageRequest {
+    /// Rows to skip.
+    Offset(i32),
+    Cursor(Cursor),
+}
+
+/// One page of a plugin listing, with cursors for its neighbours.
+#[derive(Debug)]
+pub struct PluginPage {
+    pub plugins: Vec<PluginSummary>,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
+impl AdvancedSearchRequest {
+    pub fn plugin_filters(&self) -> PluginFilters {
+        let filters = &self.filters;
+        PluginFilters {
+            search: Some(self.query.clone()).filter(|q| !q.trim().is_empty()),
+            tags: filters.tags.iter().filter(|t| !t.trim().is_empty()).cloned().collect(),
+            all_tags: filters.tags_match == TagMatch::All,
+            author: filters.author.clone(),
+            os: filters.os.clone(),
+            arch: filters.arch.clone(),
+            requires: filters.requires.clone(),
+            provides: filters.provides.clone(),
+            license: filters.license.clone(),
+            osi_approved: filters.osi_approved,
+            min_rating: filters.min_rating,
+            min_downloads: filters.min_downloads,
+            max_downloads: filters.max_downloads,
+            created_after: filters.created_after,
+            created_before: filters.created_before,
+            updated_after: filters.updated_after,
+            updated_before: filters.updated_before,
+            geektools_version: filters.geektools_version.clone(),
+            has_executable_scripts: filters.has_executable_scripts,
+            include_deprecated: !filters.exclude_deprecated.unwrap_or(true),
+        }
+    }
+}
+
+fn validate_search_filters(filters: &SearchFilters) -> Result<(), ValidationError> {
+    let invalid = |message: String| {
+        let mut error = ValidationError::new("filters");
+        error.message = Some(message.into());
+        error
+    };
+
+    if let (Some(min), Some(max)) = (filters.min_downloads, filters.max_downloads) {
+        if min > max {
+            return Err(invalid("min_downloads cannot be greater than max_downloads".to_string()));
+        }
+    }
+    if let (Some(after), Some(before)) = (filters.created_after, filters.created_before) {
+        if after > before {
+            return Err(invalid("created_after cannot be later than created_before".to_string()));
+        }
+    }
+    if let (Some(after), Some(before)) = (filters.updated_after, filters.updated_before) {
+        if after > before {
+            return Err(invalid("updated_after cannot be later than updated_before".to_string()));
+        }
+    }
+    if let Some(version) = &filters.geektools_version {
+        validation::validate_version(version).map_err(invalid)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    models::{ClickThroughStats, DailyClickThrough, QueryStats, SearchLog, ZeroResultQuery},
+    utils::search,
+};
+
+/// Share of searches that are recorded. Reports count the sample, so absolute numbers are
+/// roughly this fraction of real traffic while rates are unaffected.
+pub const SEARCH_LOG_SAMPLE_RATE: f64 = 0.25;
+/// Clicks later than this after a search are not attributed to it.
+const CLICK_WINDOW_HOURS: i32 = 24;
+
+pub struct SearchAnalyticsService {
+    db_pool: PgPool,
+}
+
+impl SearchAnalyticsService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Record a sample of searches in the background. Returns the ID that clicks on the
+    /// results should carry, or `None` when the search is not recorded. Plain listings
+    /// without a query or filters are never recorded.
+    pub fn log_search(self: &Arc<Self>, entry: SearchLog) -> Option<Uuid> {
+        let query = search::normalize_query(&entry.query);
+        // Only the filters that were set
+        let mut filters = entry.filters.as_object().cloned().unwrap_or_default();
+        filters.retain(|_, value| match value {
+            serde_json::Value::Null => false,
+            serde_json::Value::String(s) => !s.trim().is_empty(),
+            serde_json::Value::Array(items) => !items.is_empty(),
+            _ => true,
+        });
+        if (query.is_empty() && filters.is_empty()) || fastrand::f64() >= SEARCH_LOG_SAMPLE_RATE {
+            return None;
+        }
+        let filters = serde_json::Value::Object(filters);
+
+        let id = Uuid::new_v4();
+        let service = self.clone();
+        tokio::spawn(async move {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO search_queries (id, source, query, filters, result_count, result_ids)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#
+            )
+            .bind(id)
+            .bind(entry.source)
+            .bind(&query)
+            .bind(&filters)
+            .bind(entry.result_count)
+            .bind(&entry.result_ids)
+            .execute(&service.db_pool)
+            .await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to log search: {}", e);
+            }
+        });
+        Some(id)
+    }
+
+    /// Attribute a detail view or download to the search it came from. Only the first click
+    /// on one of the search's results counts.
+    pub fn record_click(self: &Arc<Self>, search_id: Uuid, plugin_id: String) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let result = sqlx::query(
+                r#"
+                UPDATE search_queries
+                SET clicked_plugin_id = $2, clicked_position = array_position(result_ids, $2::text),
+                    clicked_at = CURRENT_TIMESTAMP
+                WHERE id = $1 AND clicked_plugin_id IS NULL AND $2 = ANY(result_ids)
+                  AND created_at > CURRENT_TIMESTAMP - make_interval(hours => $3)
+                "#
+            )
+            .bind(search_id)
+            .bind(&plugin_id)
+            .bind(CLICK_WINDOW_HOURS)
+            .execute(&service.db_pool)
+            .await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to record search click: {}", e);
+            }
+        });
+    }
+
+    /// The most searched queries of the last `days` days.
+    pub async fn top_queries(&self, days: i32, limit: i64) -> sqlx::Result<Vec<QueryStats>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT query, COUNT(*) AS searches, avg(result_count)::float8 AS average_results,
+                   COUNT(clicked_plugin_id) AS clicks
+            FROM search_queries
+            WHERE query <> '' AND created_at > CURRENT_TIMESTAMP - make_interval(days => $1)
+            GROUP BY query
+            ORDER BY searches DESC, query
+            LIMIT $2
+            "#
+        )
+        .bind(days)
+        .bind(limit)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let searches: i64 = row.get("searches");
+                let clicks: i64 = row.get("clicks");
+                QueryStats {
+                    query: row.get("query"),
+                    searches,
+                    average_results: row.get("average_results"),
+                    clicks,
+                    click_through_rate: rate(clicks, searches),
+                }
+            })
+            .collect())
+    }
+
+    /// The most searched queries of the last `days` days that found nothing.
+    pub async fn zero_result_queries(&self, days: i32, limit: i64) -> sqlx::Result<Vec<ZeroResultQuery>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT query, COUNT(*) AS searches, max(created_at) AS last_searched_at
+            FROM search_queries
+            WHERE query <> '' AND result_count = 0 AND created_at > CURRENT_TIMESTAMP - make_interval(days => $1)
+            GROUP BY query
+            ORDER BY searches DESC, last_searched_at DESC
+            LIMIT $2
+            "#
+        )
+        .bind(days)
+        .bind(limit)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ZeroResultQuery {
+                query: row.get("query"),
+                searches: row.get("searches"),
+                last_searched_at: row.get("last_searched_at"),
+            })
+            .collect())
+    }
+
+    /// Click-through of searches with results over the last `days` days, overall and per day.
+    pub async fn click_through(&self, days: i32) -> sqlx::Result<ClickThroughStats> {
+        let rows = sqlx::query(
+            r#"
+            SELECT (created_at AT TIME ZONE 'UTC')::date AS day, COUNT(*) AS searches,
+                   COUNT(clicked_plugin_id) AS clicks,
+                   sum(clicked_position) AS position_sum, COUNT(clicked_position) AS positions
+            FROM search_queries
+            WHERE result_count > 0 AND created_at > CURRENT_TIMESTAMP - make_interval(days => $1)
+            GROUP BY day
+            ORDER BY day
+            "#
+        )
+        .bind(days)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let (mut searches, mut clicks, mut position_sum, mut positions) = (0, 0, 0, 0);
+        let daily = rows
+            .into_iter()
+            .map(|row| {
+                let day_searches: i64 = row.get("searches");
+                let day_clicks: i64 = row.get("clicks");
+                searches += day_searches;
+                clicks += day_clicks;
+                position_sum += row.get::<Option<i64>, _>("position_sum").unwrap_or(0);
+                positions += row.get::<i64, _>("positions");
+                DailyClickThrough {
+                    day: row.get("day"),
+                    searches: day_searches,
+                    clicks: day_clicks,
+                    click_through_rate: rate(day_clicks, day_searches),
+                }
+            })
+            .collect();
+
+        Ok(ClickThroughStats {
+            searches,
+            clicks,
+            click_through_rate: rate(clicks, searches),
+            average_click_position: (positions > 0).then(|| position_sum as f64 / positions as f64),
+            daily,
+        })
+    }
+}
+
+fn rate(count: i64, total: i64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
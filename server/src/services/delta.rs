@@ -0,0 +1,164 @@
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+
+use crate::{services::StorageService, utils::delta};
+
+/// How many earlier stable versions get a delta to each new version.
+const MAX_DELTA_BASES: i64 = 3;
+
+/// A stored delta, the archive it applies to and what applying it must produce. Both are the
+/// archives exactly as a full download serves them.
+#[derive(Debug, Clone)]
+pub struct DeltaInfo {
+    pub file_path: String,
+    pub from_version: String,
+    pub to_version: String,
+    pub base_hash: String,
+    pub result_hash: String,
+    pub result_size: i64,
+}
+
+pub struct DeltaService {
+    db_pool: PgPool,
+    storage_service: Arc<StorageService>,
+}
+
+impl DeltaService {
+    pub fn new(db_pool: PgPool, storage_service: Arc<StorageService>) -> Self {
+        Self {
+            db_pool,
+            storage_service,
+        }
+    }
+
+    /// Build deltas to a freshly published version in the background; failures are only logged,
+    /// clients fall back to the full archive.
+    pub fn schedule(self: &Arc<Self>, plugin_id: String, version: String) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            match service.generate_deltas(&plugin_id, &version).await {
+                Ok(count) => tracing::info!("Generated {} delta(s) for {} {}", count, plugin_id, version),
+                Err(e) => tracing::warn!("Failed to generate deltas for {} {}: {}", plugin_id, version, e),
+            }
+        });
+    }
+
+    async fn generate_deltas(&self, plugin_id: &str, version: &str) -> anyhow::Result<usize> {
+        let target = sqlx::query(
+            r#"
+            SELECT file_path, created_at FROM plugin_versions
+            WHERE plugin_id = $1 AND version = $2
+            "#
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("version not found"))?;
+
+        let bases = sqlx::query(
+            r#"
+            SELECT version, file_path FROM plugin_versions
+            WHERE plugin_id = $1 AND version <> $2 AND is_stable = true AND created_at < $3
+            ORDER BY created_at DESC
+            LIMIT $4
+            "#
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .bind(target.get::<chrono::DateTime<chrono::Utc>, _>("created_at"))
+        .bind(MAX_DELTA_BASES)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        if bases.is_empty() {
+            return Ok(0);
+        }
+
+        // Deltas go between the archives as served, so that a client can patch what it downloaded
+        let target_archive = tokio::fs::read(target.get::<String, _>("file_path")).await?;
+        let result_hash = hex::encode(Sha256::digest(&target_archive));
+        let result_size = target_archive.len() as i64;
+        let target_archive = Arc::new(target_archive);
+
+        let mut generated = 0;
+        for base in bases {
+            let from_version: String = base.get("version");
+            let base_archive = tokio::fs::read(base.get::<String, _>("file_path")).await?;
+
+            let target = target_archive.clone();
+            let patch = tokio::task::spawn_blocking(move || delta::build_delta(&base_archive, &target)).await??;
+
+            // Not worth serving when the full archive is about as small
+            if patch.len() >= target_archive.len() {
+                continue;
+            }
+
+            let file_size = patch.len() as i64;
+            let file_path = self
+                .storage_service
+                .store_delta_file(patch, plugin_id, &from_version, version)
+                .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO plugin_deltas (plugin_id, from_version, to_version, file_path, file_size,
+                                           result_hash, result_size)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (plugin_id, from_version, to_version)
+                DO UPDATE SET file_path = EXCLUDED.file_path, file_size = EXCLUDED.file_size,
+                              result_hash = EXCLUDED.result_hash, result_size = EXCLUDED.result_size,
+                              created_at = NOW()
+                "#
+            )
+            .bind(plugin_id)
+            .bind(&from_version)
+            .bind(version)
+            .bind(&file_path)
+            .bind(file_size)
+            .bind(&result_hash)
+            .bind(result_size)
+            .execute(&self.db_pool)
+            .await?;
+
+            generated += 1;
+        }
+
+        Ok(generated)
+    }
+
+    /// The delta from `from_version` to `to_version`, or to the current version when not given.
+    pub async fn find_delta(
+        &self,
+        plugin_id: &str,
+        from_version: &str,
+        to_version: Option<&str>,
+    ) -> sqlx::Result<Option<DeltaInfo>> {
+        let row = sqlx::query(
+            r#"
+            SELECT d.file_path, d.from_version, d.to_version, fv.file_hash AS base_hash,
+                   d.result_hash, d.result_size
+            FROM plugin_deltas d
+            JOIN plugins p ON d.plugin_id = p.id
+            JOIN plugin_versions fv ON fv.plugin_id = d.plugin_id AND fv.version = d.from_version
+            WHERE d.plugin_id = $1 AND d.from_version = $2
+              AND d.to_version = COALESCE($3, p.current_version)
+            "#
+        )
+        .bind(plugin_id)
+        .bind(from_version)
+        .bind(to_version)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(row.map(|row| DeltaInfo {
+            file_path: row.get("file_path"),
+            from_version: row.get("from_version"),
+            to_version: row.get("to_version"),
+            base_hash: row.get("base_hash"),
+            result_hash: row.get("result_hash"),
+            result_size: row.get("result_size"),
+        }))
+    }
+}
@@ -1,4 +1,6 @@
+pub mod analytics;
 pub mod auth;
+pub mod delta;
 pub mod plugin;
 pub mod storage;
 pub mod admin;
@@ -8,7 +10,9 @@ use sqlx::PgPool;
 use std::sync::Arc;
 
 use crate::utils::config::Config;
+use analytics::SearchAnalyticsService;
 use auth::AuthService;
+use delta::DeltaService;
 use plugin::PluginService;
 use storage::StorageService;
 use admin::AdminService;
@@ -21,8 +25,10 @@ pub struct AppState {
     pub auth_service: Arc<AuthService>,
     pub plugin_service: Arc<PluginService>,
     pub storage_service: Arc<StorageService>,
+    pub delta_service: Arc<DeltaService>,
     pub admin_service: Arc<AdminService>,
     pub smtp_service: Arc<SmtpService>,
+    pub search_analytics: Arc<SearchAnalyticsService>,
 }
 
 impl AppState {
@@ -30,13 +36,25 @@ impl AppState {
         let config = Arc::new(config);
         let storage_service = Arc::new(StorageService::new(config.clone())?);
         let auth_service = Arc::new(AuthService::new(db_pool.clone(), config.clone()));
+        let delta_service = Arc::new(DeltaService::new(db_pool.clone(), storage_service.clone()));
         let plugin_service = Arc::new(PluginService::new(
             db_pool.clone(),
             storage_service.clone(),
+            delta_service.clone(),
             config.clone(),
         ));
+        let indexed = plugin_service.index_unindexed_plugins().await?;
+        if indexed > 0 {
+            tracing::info!("Indexed {} plugin(s) for CJK search", indexed);
+        }
+        let indexed = plugin_service.index_unindexed_scripts().await?;
+        if indexed > 0 {
+            tracing::info!("Indexed {} script(s) for search", indexed);
+        }
+        plugin_service.schedule_score_refresh();
         let admin_service = Arc::new(AdminService::new(db_pool.clone(), config.clone()));
         let smtp_service = Arc::new(SmtpService::new(config.smtp.clone()));
+        let search_analytics = Arc::new(SearchAnalyticsService::new(db_pool.clone()));
 
         Ok(Self {
             db_pool,
@@ -44,8 +62,10 @@ impl AppState {
             auth_service,
             plugin_service,
             storage_service,
+            delta_service,
             admin_service,
             smtp_service,
+            search_analytics,
         })
     }
 }
\ No newline at end of file
@@ -1,24 +1,60 @@
-use flate2::read::GzDecoder;
 use sha2::{Digest, Sha256};
-use sqlx::{PgPool, Row};
-use sqlx::types::{BigDecimal, Decimal};
-use std::{collections::HashMap, io::Read, sync::Arc, str::FromStr};
-use tar::Archive;
-use validator::Validate;
+use sqlx::{Executor, PgPool, Postgres, Row, Transaction};
+use sqlx::types::{BigDecimal, Decimal, Json};
+use std::{cmp::Ordering, collections::{BTreeMap, HashMap}, sync::Arc, str::FromStr};
 
 use crate::{
     models::{
-        CreatePluginRequest, Plugin, PluginDetailResponse, PluginDependencyInfo,
-        PluginScriptInfo, PluginStatsResponse, PluginSummary, PluginVersion,
-        PluginVersionInfo, RatingResponse, UploadResponse,
+        ConfigBreakingChange, CreatePluginRequest, FilesystemPermission, FundingLink, InstalledPlugin, LicenseCount, LicenseStatsResponse, ManifestIssue, Plugin, PluginArtifactInfo,
+        PluginDetailResponse, PluginDependencyInfo, PluginDiffResponse, PluginFileInfo,
+        PluginFilesResponse, PluginConfigResponse, PluginConfigSchema, PluginConflict, PluginFilters, PluginHighlights, PluginHooks, PluginImage, PluginPermissions, PluginPlatform, PluginScriptInfo, PluginStatsResponse,
+        PageRequest, PluginPage, PluginSummary, PluginUpdateInfo, PluginVersion, PluginVersionInfo, RatingBucket, RatingResponse, RelatedPlugin, ScriptHighlights, ScriptSearchResult, FacetCount, SearchFacet, SearchFacets, UploadResponse, ValidationReport,
+    },
+    services::{DeltaService, StorageService},
+    utils::{
+        config::Config,
+        diff, i18n, license, manifest,
+        media::{self, MediaKind, ProcessedImage},
+        package::{PackageFormat, PluginPackage},
+        pagination::Cursor,
+        plugin_config, search,
+        version::{compare_versions, VersionRange},
     },
-    services::StorageService,
-    utils::config::Config,
 };
 
+/// An unpacked upload whose info.json could be parsed.
+struct InspectedPackage {
+    format: PackageFormat,
+    package: Arc<PluginPackage>,
+    manifest: CreatePluginRequest,
+}
+
+/// Where an upload ended up on disk, with its hashes and those of its canonical form.
+struct StoredPackage {
+    file_path: String,
+    file_hash: String,
+    file_size: i64,
+    canonical_file_path: String,
+    canonical_hash: String,
+    files: Vec<PluginFileInfo>,
+}
+
+const CANONICAL_EXTENSION: &str = "canonical.tar.gz";
+
+#[derive(Debug, thiserror::Error)]
+pub enum UploadError {
+    #[error("Plugin package failed validation")]
+    Invalid(Vec<ManifestIssue>),
+    #[error("Failed to store plugin file: {0}")]
+    Storage(String),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
 pub struct PluginService {
     db_pool: PgPool,
     storage_service: Arc<StorageService>,
+    delta_service: Arc<DeltaService>,
     config: Arc<Config>,
 }
 
@@ -26,138 +62,334 @@ impl PluginService {
     pub fn new(
         db_pool: PgPool,
         storage_service: Arc<StorageService>,
+        delta_service: Arc<DeltaService>,
         config: Arc<Config>,
     ) -> Self {
         Self {
             db_pool,
             storage_service,
+            delta_service,
             config,
         }
     }
 
+    /// Active plugins matching `filter`. Sorting by `relevance` ranks full-text matches
+    /// and falls back to downloads when there is no search; `trending` and `hot` use the
+    /// precomputed popularity scores. Every page carries cursors for
+    /// its neighbours, so clients can switch from offsets to keyset paging at any point.
     pub async fn search_plugins(
         &self,
-        query: Option<&str>,
-        tag: Option<&str>,
+        filter: &FilterSql,
         sort: &str,
         order: &str,
         limit: i32,
-        offset: i32,
-    ) -> sqlx::Result<Vec<PluginSummary>> {
-        // Simplified query for now - just get basic plugin info
-        let order_by = match sort {
-            "rating" => "rating",
-            "name" => "name", 
-            "created_at" => "created_at",
-            "updated_at" => "updated_at",
-            _ => "downloads"
+        page: &PageRequest,
+        languages: &[String],
+    ) -> sqlx::Result<PluginPage> {
+        let descending = order != "asc";
+        // Sort key expressions with their types, all ordered the same way and ending in
+        // the ID so that every row has a distinct position
+        let keys: Vec<(String, &str)> = match (sort, &filter.rank) {
+            ("relevance", Some(rank)) => vec![(format!("({})", rank), "real"), ("coalesce(p.downloads, 0)".to_string(), "integer")],
+            ("rating", _) => vec![("coalesce(p.rating, 0)".to_string(), "numeric")],
+            ("name", _) => vec![("p.name".to_string(), "text")],
+            ("created_at", _) => vec![("coalesce(p.created_at, 'epoch')".to_string(), "timestamptz")],
+            ("updated_at", _) => vec![("coalesce(p.updated_at, 'epoch')".to_string(), "timestamptz")],
+            ("trending", _) => vec![("p.trending_score".to_string(), "float8")],
+            ("hot", _) => vec![("p.hot_score".to_string(), "float8")],
+            _ => vec![("coalesce(p.downloads, 0)".to_string(), "integer")],
         };
-        
-        let order_dir = if order == "asc" { "ASC" } else { "DESC" };
-        
-        let mut where_clause = "WHERE status = 'active'".to_string();
-        
-        if let Some(search_query) = query {
-            if !search_query.trim().is_empty() {
-                where_clause.push_str(&format!(
-                    " AND (name ILIKE '%{}%' OR description ILIKE '%{}%' OR author ILIKE '%{}%')",
-                    search_query.replace("'", "''"), // Basic SQL injection prevention
-                    search_query.replace("'", "''"),
-                    search_query.replace("'", "''")
-                ));
-            }
-        }
-        
-        if let Some(tag_filter) = tag {
-            if !tag_filter.trim().is_empty() {
-                where_clause.push_str(&format!(
-                    " AND id IN (SELECT plugin_id FROM plugin_tags WHERE tag ILIKE '%{}%')",
-                    tag_filter.replace("'", "''")
-                ));
+
+        let mut binds = filter.binds.clone();
+        let mut conditions = filter.conditions.clone();
+        let (offset, before) = match page {
+            PageRequest::Offset(offset) => (*offset, false),
+            PageRequest::Cursor(cursor) => {
+                if cursor.keys.len() == keys.len() {
+                    let mut values = Vec::new();
+                    for ((_, key_type), value) in keys.iter().zip(&cursor.keys) {
+                        binds.push(value.clone());
+                        values.push(format!("${}::{}", binds.len(), key_type));
+                    }
+                    binds.push(cursor.id.clone());
+                    values.push(format!("${}", binds.len()));
+                    let columns: Vec<&str> = keys.iter().map(|(key, _)| key.as_str()).collect();
+                    conditions.push_str(&format!(
+                        " AND ({}, p.id) {} ({})",
+                        columns.join(", "),
+                        if descending != cursor.before { "<" } else { ">" },
+                        values.join(", ")
+                    ));
+                } else {
+                    // Issued before the search changed how results are ranked
+                    conditions.push_str(" AND FALSE");
+                }
+                (0, cursor.before)
             }
-        }
-        
+        };
+
+        // Pages ending before a cursor are read backwards from it, then reversed
+        let direction = if descending != before { "DESC" } else { "ASC" };
+        let order_by: Vec<String> = keys
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .chain(["p.id"])
+            .map(|key| format!("{} {}", key, direction))
+            .collect();
+        let key_text: Vec<String> = keys.iter().map(|(key, _)| format!("({})::text", key)).collect();
+
         let sql = format!(
-            "SELECT id, name, description, author, current_version, downloads, rating, created_at, updated_at 
-             FROM plugins 
-             {} 
-             ORDER BY {} {} 
-             LIMIT {} OFFSET {}",
-            where_clause, order_by, order_dir, limit, offset
+            "SELECT {}, ARRAY[{}] AS sort_keys
+             FROM plugins p
+             WHERE {}
+             ORDER BY {}
+             LIMIT ${} OFFSET ${}",
+            SUMMARY_COLUMNS,
+            key_text.join(", "),
+            conditions,
+            order_by.join(", "),
+            binds.len() + 1,
+            binds.len() + 2
         );
 
-        let rows = sqlx::query(&sql)
-            .fetch_all(&self.db_pool)
+        let mut query = sqlx::query(&sql);
+        for value in &binds {
+            query = query.bind(value);
+        }
+        // One extra row tells whether there is another page
+        let mut tx = self.begin_search(filter.fuzzy).await?;
+        let mut rows = query
+            .bind(limit as i64 + 1)
+            .bind(offset as i64)
+            .fetch_all(&mut *tx)
             .await?;
+        tx.commit().await?;
+        let more = rows.len() > limit as usize;
+        rows.truncate(limit as usize);
+        if before {
+            rows.reverse();
+        }
 
-        let mut plugins = Vec::new();
-        for row in rows {
-            // Convert NUMERIC to f64
-            let rating: Option<sqlx::types::Decimal> = row.try_get("rating").ok();
-            let rating_decimal = rating.map(|d| BigDecimal::from_str(&d.to_string()).unwrap_or_else(|_| BigDecimal::from_str("0.00").unwrap()))
-                .unwrap_or_else(|| BigDecimal::from_str("0.00").unwrap());
-            
-            plugins.push(PluginSummary {
+        let cursor_at = |row: &sqlx::postgres::PgRow, before: bool| {
+            Cursor {
+                sort: sort.to_string(),
+                order: order.to_string(),
+                keys: row.get("sort_keys"),
                 id: row.get("id"),
-                name: row.get("name"),
-                description: row.get("description"),
-                author: row.get("author"),
-                current_version: row.get("current_version"),
-                downloads: row.get("downloads"),
-                rating: rating_decimal,
-                tags: vec![], // Empty for now
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            });
+                before,
+            }
+            .encode()
+        };
+        let (has_prev, has_next) = match page {
+            PageRequest::Offset(offset) => (*offset > 0, more),
+            PageRequest::Cursor(cursor) if cursor.before => (more, true),
+            PageRequest::Cursor(_) => (true, more),
+        };
+        let prev_cursor = rows.first().filter(|_| has_prev).map(|row| cursor_at(row, true));
+        let next_cursor = rows.last().filter(|_| has_next).map(|row| cursor_at(row, false));
+
+        let mut plugins: Vec<PluginSummary> = rows.iter().map(summary_from_row).collect();
+
+        self.localize_summaries(&mut plugins, languages).await?;
+        self.attach_icons(&mut plugins).await?;
+
+        // Highlight after localizing, so the marks land in the text that is returned
+        if !filter.terms.is_empty() {
+            for plugin in &mut plugins {
+                plugin.highlights = Some(PluginHighlights {
+                    name: search::highlight(&plugin.name, &filter.terms),
+                    description: plugin
+                        .description
+                        .as_deref()
+                        .and_then(|d| search::highlight_fragment(d, &filter.terms, HIGHLIGHT_WORDS)),
+                });
+            }
         }
 
-        Ok(plugins)
+        Ok(PluginPage { plugins, next_cursor, prev_cursor })
     }
 
-    pub async fn count_plugins(
-        &self,
-        query: Option<&str>,
-        tag: Option<&str>,
-    ) -> sqlx::Result<i64> {
-        let mut sql = String::from(
-            "SELECT COUNT(DISTINCT p.id) FROM plugins p LEFT JOIN plugin_tags pt ON p.id = pt.plugin_id WHERE p.status = 'active'"
+    pub async fn count_plugins(&self, filter: &FilterSql) -> sqlx::Result<i64> {
+        match filter.count {
+            Some(count) => Ok(count),
+            None => self.count_matching(filter).await,
+        }
+    }
+
+    async fn count_matching(&self, filter: &FilterSql) -> sqlx::Result<i64> {
+        let sql = format!(
+            "SELECT COUNT(*) FROM plugins p WHERE {}",
+            filter.conditions
         );
 
-        let mut conditions = Vec::new();
-        let mut bind_index = 1;
-        let mut bind_params: Vec<String> = Vec::new();
+        let mut query = sqlx::query_scalar(&sql);
+        for value in &filter.binds {
+            query = query.bind(value);
+        }
+        let mut tx = self.begin_search(filter.fuzzy).await?;
+        let count = query.fetch_one(&mut *tx).await?;
+        tx.commit().await?;
+        Ok(count)
+    }
+
+    /// A transaction for searching. With `fuzzy`, the pg_trgm thresholds are set to
+    /// `FUZZY_SIMILARITY` and `DID_YOU_MEAN_SIMILARITY`, so that the indexable `<%` and `%`
+    /// operators select exactly what those allow.
+    async fn begin_search(&self, fuzzy: bool) -> sqlx::Result<Transaction<'static, Postgres>> {
+        let mut tx = self.db_pool.begin().await?;
+        if fuzzy {
+            let thresholds = format!(
+                "SET LOCAL pg_trgm.word_similarity_threshold = {}; SET LOCAL pg_trgm.similarity_threshold = {}",
+                FUZZY_SIMILARITY, DID_YOU_MEAN_SIMILARITY
+            );
+            tx.execute(thresholds.as_str()).await?;
+        }
+        Ok(tx)
+    }
 
-        if let Some(q) = query {
-            conditions.push(format!(
-                "(p.name ILIKE ${} OR p.description ILIKE ${})",
-                bind_index, bind_index + 1
-            ));
-            let search_pattern = format!("%{}%", q);
-            bind_params.push(search_pattern.clone());
-            bind_params.push(search_pattern);
-            bind_index += 2;
+    /// Conditions for `filters`, falling back to fuzzy matching when a search finds fewer
+    /// than `FUZZY_FALLBACK_BELOW` plugins. Resolve once per request and pass the result to
+    /// searching, counting and facets, so that they all agree on the fallback.
+    pub async fn resolve_filters(&self, filters: &PluginFilters) -> sqlx::Result<FilterSql> {
+        let filter = FilterSql::new(filters, false);
+        if filter.rank.is_none() {
+            return Ok(filter);
         }
+        let count = self.count_matching(&filter).await?;
+        Ok(FilterSql::with_fallback(filters, filter, count))
+    }
 
-        if let Some(t) = tag {
-            conditions.push(format!("pt.tag = ${}", bind_index));
-            bind_params.push(t.to_string());
+    /// Counts per value of each of `facets` among the plugins matching `filter`, all
+    /// computed by one query over the matching set.
+    pub async fn search_facets(&self, filter: &FilterSql, facets: &[SearchFacet]) -> sqlx::Result<SearchFacets> {
+        let mut result = SearchFacets::default();
+        if facets.is_empty() {
+            return Ok(result);
         }
 
-        if !conditions.is_empty() {
-            sql.push_str(" AND ");
-            sql.push_str(&conditions.join(" AND "));
+        let mut selects = Vec::new();
+        for facet in [SearchFacet::Tags, SearchFacet::License, SearchFacet::Author, SearchFacet::Platform, SearchFacet::Rating] {
+            if !facets.contains(&facet) {
+                continue;
+            }
+            let select = match facet {
+                SearchFacet::Tags => "SELECT 'tags', lower(pt.tag), COUNT(DISTINCT m.id)
+                     FROM matched m JOIN plugin_tags pt ON pt.plugin_id = m.id GROUP BY 2",
+                SearchFacet::License => "SELECT 'license', lid, COUNT(*) FROM matched m, unnest(m.license_ids) lid GROUP BY 2",
+                SearchFacet::Author => "SELECT 'author', m.author, COUNT(*) FROM matched m GROUP BY 2",
+                // Counted the way the os filter matches, so a count is what selecting it returns
+                SearchFacet::Platform => "SELECT 'platform', os.os, COUNT(*)
+                     FROM (SELECT DISTINCT pp.os FROM matched m
+                           JOIN plugin_platforms pp ON pp.plugin_id = m.id AND pp.version = m.current_version) os
+                     JOIN matched m ON NOT EXISTS (SELECT 1 FROM plugin_platforms pp WHERE pp.plugin_id = m.id AND pp.version = m.current_version)
+                         OR EXISTS (SELECT 1 FROM plugin_platforms pp WHERE pp.plugin_id = m.id AND pp.version = m.current_version AND pp.os = os.os)
+                     GROUP BY 2",
+                SearchFacet::Rating => "SELECT 'rating', b::text, COUNT(m.id)
+                     FROM generate_series(1, 4) b LEFT JOIN matched m ON m.rating >= b GROUP BY b",
+            };
+            selects.push(format!("({} ORDER BY 3 DESC, 2 LIMIT {})", select, FACET_VALUES));
         }
 
-        let mut query_builder = sqlx::query_scalar(&sql);
+        let sql = format!(
+            "WITH matched AS MATERIALIZED (
+                 SELECT p.id, p.author, p.license_ids, p.rating, p.current_version FROM plugins p WHERE {}
+             )
+             {}",
+            filter.conditions,
+            selects.join(" UNION ALL ")
+        );
+
+        let mut query = sqlx::query_as::<_, (String, String, i64)>(&sql);
+        for value in &filter.binds {
+            query = query.bind(value);
+        }
+        let mut tx = self.begin_search(filter.fuzzy).await?;
+        let rows = query.fetch_all(&mut *tx).await?;
+        tx.commit().await?;
 
-        for param in bind_params {
-            query_builder = query_builder.bind(param);
+        for facet in facets {
+            match facet {
+                SearchFacet::Tags => result.tags = Some(Vec::new()),
+                SearchFacet::License => result.license = Some(Vec::new()),
+                SearchFacet::Author => result.author = Some(Vec::new()),
+                SearchFacet::Platform => result.platform = Some(Vec::new()),
+                SearchFacet::Rating => result.rating = Some(Vec::new()),
+            }
+        }
+        for (facet, value, count) in rows {
+            let counts = match facet.as_str() {
+                "tags" => &mut result.tags,
+                "license" => &mut result.license,
+                "author" => &mut result.author,
+                "platform" => &mut result.platform,
+                _ => {
+                    if let (Some(buckets), Ok(min_rating)) = (&mut result.rating, value.parse()) {
+                        buckets.push(RatingBucket { min_rating, count });
+                    }
+                    continue;
+                }
+            };
+            if let Some(counts) = counts {
+                counts.push(FacetCount { value, count });
+            }
         }
+        if let Some(buckets) = &mut result.rating {
+            buckets.sort_by_key(|bucket| std::cmp::Reverse(bucket.min_rating));
+        }
+
+        Ok(result)
+    }
+
+    /// Active plugins per license ID, most used first.
+    pub async fn get_license_stats(&self) -> sqlx::Result<LicenseStatsResponse> {
+        let rows = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT lid, COUNT(*) AS plugins
+            FROM plugins, unnest(license_ids) lid
+            WHERE status = 'active'
+            GROUP BY lid
+            ORDER BY plugins DESC, lid
+            "#
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let licenses = rows
+            .into_iter()
+            .map(|(id, plugins)| LicenseCount {
+                osi_approved: license::is_osi_approved(&id),
+                license: id,
+                plugins,
+            })
+            .collect();
+
+        let (custom, unlicensed) = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT COUNT(*) FILTER (WHERE license IS NOT NULL AND NOT license_spdx),
+                   COUNT(*) FILTER (WHERE license IS NULL)
+            FROM plugins WHERE status = 'active'
+            "#
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(LicenseStatsResponse { licenses, custom, unlicensed })
+    }
 
-        query_builder.fetch_one(&self.db_pool).await
+    /// The author of a plugin, or `None` when it does not exist.
+    pub async fn get_plugin_author(&self, plugin_id: &str) -> sqlx::Result<Option<String>> {
+        sqlx::query_scalar("SELECT author FROM plugins WHERE id = $1")
+            .bind(plugin_id)
+            .fetch_optional(&self.db_pool)
+            .await
     }
 
-    pub async fn get_plugin_detail(&self, plugin_id: &str) -> sqlx::Result<Option<PluginDetailResponse>> {
+    /// Plugin details, with name, description and README in the best match for `languages`.
+    pub async fn get_plugin_detail(
+        &self,
+        plugin_id: &str,
+        languages: &[String],
+    ) -> sqlx::Result<Option<PluginDetailResponse>> {
         let row = sqlx::query(
             "SELECT * FROM plugins WHERE id = $1 AND status = 'active'"
         )
@@ -176,13 +408,39 @@ impl PluginService {
             
             let versions = self.get_plugin_versions(&plugin_id).await?;
             let scripts = self.get_plugin_scripts(&plugin_id, &current_version).await?;
-            let dependencies = self.get_plugin_dependencies(&plugin_id).await?;
+            let dependencies = self.get_plugin_dependencies(&plugin_id, &current_version).await?;
+            let conflicts = self.get_plugin_conflicts(&plugin_id, &current_version).await?;
+            let provides = self.get_plugin_provides(&plugin_id, &current_version).await?;
             let tags = self.get_plugin_tags(&plugin_id).await?;
+            let platforms = self.get_plugin_platforms(&plugin_id, &current_version).await?;
+            let requires = self.get_plugin_requirements(&plugin_id, &current_version).await?;
+            let permissions = self.get_plugin_permissions(&plugin_id, &current_version).await?;
+            let config = self.get_plugin_config_schema(&plugin_id, &current_version).await?;
+            let hooks = self.get_plugin_hooks(&plugin_id, &current_version).await?;
+
+            let (default_locale, default_readme, localizations) =
+                self.get_plugin_localizations(&plugin_id, &current_version).await?;
+            let available_locales = default_locale
+                .iter()
+                .chain(localizations.iter().map(|l| &l.locale))
+                .cloned()
+                .collect();
+            let (icon, screenshots) = self.get_plugin_media(&plugin_id, &current_version).await?;
+            let mut name: String = row.get("name");
+            let mut description: Option<String> = row.get("description");
+            let mut readme = default_readme;
+            let mut locale = default_locale.clone();
+            if let Some(localization) = pick_localization(languages, default_locale.as_deref(), &localizations) {
+                name = localization.name.clone().unwrap_or(name);
+                description = localization.description.clone().or(description);
+                readme = localization.readme.clone().or(readme);
+                locale = Some(localization.locale.clone());
+            }
 
             Ok(Some(PluginDetailResponse {
                 id: row.get("id"),
-                name: row.get("name"),
-                description: row.get("description"),
+                name,
+                description,
                 author: row.get("author"),
                 current_version,
                 downloads: row.get("downloads"),
@@ -191,10 +449,28 @@ impl PluginService {
                 min_geektools_version: row.get("min_geektools_version"),
                 homepage_url: row.get("homepage_url"),
                 repository_url: row.get("repository_url"),
+                issues_url: row.get("issues_url"),
+                docs_url: row.get("docs_url"),
+                support_email: row.get("support_email"),
+                funding: row.get::<Json<Vec<FundingLink>>, _>("funding").0,
                 license: row.get("license"),
+                license_spdx: row.get("license_spdx"),
+                license_osi_approved: row.get("license_osi_approved"),
                 versions,
                 scripts,
                 dependencies,
+                conflicts,
+                provides,
+                platforms,
+                requires,
+                permissions,
+                config,
+                hooks,
+                locale,
+                available_locales,
+                readme,
+                icon,
+                screenshots,
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
             }))
@@ -203,39 +479,41 @@ impl PluginService {
         }
     }
 
+    /// Run the full upload validation on a package without persisting anything.
+    pub async fn validate_plugin(&self, data: Vec<u8>) -> Result<ValidationReport, UploadError> {
+        let (_, inspected, mut errors) = inspect_package(data).await?;
+        let manifest = inspected.map(|inspected| inspected.manifest);
+        if let Some(manifest) = &manifest {
+            errors.extend(self.check_publishable(manifest).await?);
+        }
+
+        Ok(ValidationReport {
+            valid: errors.is_empty(),
+            manifest_version: manifest.as_ref().map(|m| m.manifest_version),
+            plugin_id: manifest.as_ref().map(|m| m.id.clone()),
+            version: manifest.map(|m| m.version),
+            errors,
+        })
+    }
+
     pub async fn upload_plugin(
         &self,
         data: Vec<u8>,
         _user_id: i32,
         upload_id: &str,
-    ) -> sqlx::Result<UploadResponse> {
-        // Store file temporarily
-        let temp_file = self.storage_service.store_temporary_file(data.clone()).await
-            .map_err(|e| sqlx::Error::Protocol(format!("Failed to store temp file: {}", e)))?;
-
-        // Extract and validate plugin
-        let plugin_info = match self.extract_and_validate_plugin(&temp_file).await {
-            Ok(info) => info,
-            Err(e) => {
-                // If extraction fails, create a basic plugin info from filename
-                tracing::warn!("Plugin extraction failed: {}, creating basic info", e);
-                let plugin_id = format!("plugin_{}", uuid::Uuid::new_v4().to_string().replace("-", "")[..8].to_string());
-                CreatePluginRequest {
-                    id: plugin_id.clone(),
-                    name: format!("未知插件 {}", &plugin_id[7..]), // Use part after "plugin_" prefix
-                    description: Some("上传的插件包，无法解析插件信息".to_string()),
-                    author: "未知作者".to_string(),
-                    version: "1.0.0".to_string(),
-                    min_geektools_version: None,
-                    homepage_url: None,
-                    repository_url: None,
-                    license: None,
-                    tags: vec!["uploaded".to_string()],
-                    scripts: vec![], // Empty for now
-                    dependencies: vec![],
-                }
-            }
+    ) -> Result<UploadResponse, UploadError> {
+        // Extract and validate plugin; any problem rejects the upload
+        let (data, inspected, mut issues) = inspect_package(data).await?;
+        let inspected = match inspected {
+            Some(inspected) if issues.is_empty() => inspected,
+            _ => return Err(UploadError::Invalid(issues)),
         };
+        let plugin_info = &inspected.manifest;
+
+        issues.extend(self.check_publishable(plugin_info).await?);
+        if !issues.is_empty() {
+            return Err(UploadError::Invalid(issues));
+        }
 
         // Check if plugin already exists
         let existing_plugin = sqlx::query_as::<_, Plugin>(
@@ -245,33 +523,23 @@ impl PluginService {
         .fetch_optional(&self.db_pool)
         .await?;
 
-        if let Some(_) = existing_plugin {
-            // Check if version already exists
-            let existing_version = sqlx::query_as::<_, PluginVersion>(
-                "SELECT * FROM plugin_versions WHERE plugin_id = $1 AND version = $2"
-            )
-            .bind(&plugin_info.id)
-            .bind(&plugin_info.version)
-            .fetch_optional(&self.db_pool)
-            .await?;
-
-            if existing_version.is_some() {
-                return Err(sqlx::Error::Protocol(format!(
-                    "Version {} already exists for plugin {}",
-                    plugin_info.version, plugin_info.id
-                )));
-            }
-        }
-
-        // Calculate file hash
-        let file_hash = self.calculate_file_hash(&data);
-        let file_size = data.len();
+        // Already validated, this only normalizes it
+        let license = plugin_info
+            .license
+            .as_deref()
+            .and_then(|text| license::parse_license(text, inspected.package.license_file().is_some()).ok());
 
         // Store plugin file permanently
-        let file_path = self.storage_service
-            .store_plugin_file(data, &plugin_info.id, &plugin_info.version)
-            .await
-            .map_err(|e| sqlx::Error::Protocol(format!("Failed to store plugin file: {}", e)))?;
+        let stored = self.store_package(data, &inspected, None).await?;
+
+        let images = media::package_media(&inspected.package)
+            .into_iter()
+            .map(|(kind, entry)| (kind, entry.path.clone(), entry.data.clone()))
+            .collect();
+        let mut stored_media = Vec::new();
+        for (kind, image) in process_images(images).await? {
+            stored_media.push(self.store_media(&plugin_info.id, &plugin_info.version, kind, image).await?);
+        }
 
         // Save to database
         let mut tx = self.db_pool.begin().await?;
@@ -281,8 +549,10 @@ impl PluginService {
             sqlx::query(
                 r#"
                 INSERT INTO plugins (id, name, description, author, current_version, 
-                                   min_geektools_version, homepage_url, repository_url, license)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                                   min_geektools_version, homepage_url, repository_url, license,
+                                   license_spdx, license_osi_approved, license_ids,
+                                   issues_url, docs_url, support_email, funding)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
                 "#
             )
             .bind(&plugin_info.id)
@@ -293,15 +563,36 @@ impl PluginService {
             .bind(&plugin_info.min_geektools_version)
             .bind(&plugin_info.homepage_url)
             .bind(&plugin_info.repository_url)
-            .bind(&plugin_info.license)
+            .bind(license.as_ref().map(|l| &l.expression))
+            .bind(license.as_ref().is_some_and(|l| l.spdx))
+            .bind(license.as_ref().is_some_and(|l| l.osi_approved))
+            .bind(license.as_ref().map(|l| l.ids.clone()).unwrap_or_default())
+            .bind(&plugin_info.issues_url)
+            .bind(&plugin_info.docs_url)
+            .bind(&plugin_info.support_email)
+            .bind(Json(&plugin_info.funding))
             .execute(&mut *tx)
             .await?;
         } else {
             sqlx::query(
-                "UPDATE plugins SET current_version = $1, updated_at = CURRENT_TIMESTAMP WHERE id = $2"
+                r#"
+                UPDATE plugins SET current_version = $1, license = $3, license_spdx = $4,
+                                   license_osi_approved = $5, license_ids = $6, issues_url = $7,
+                                   docs_url = $8, support_email = $9, funding = $10,
+                                   updated_at = CURRENT_TIMESTAMP
+                WHERE id = $2
+                "#
             )
             .bind(&plugin_info.version)
             .bind(&plugin_info.id)
+            .bind(license.as_ref().map(|l| &l.expression))
+            .bind(license.as_ref().is_some_and(|l| l.spdx))
+            .bind(license.as_ref().is_some_and(|l| l.osi_approved))
+            .bind(license.as_ref().map(|l| l.ids.clone()).unwrap_or_default())
+            .bind(&plugin_info.issues_url)
+            .bind(&plugin_info.docs_url)
+            .bind(&plugin_info.support_email)
+            .bind(Json(&plugin_info.funding))
             .execute(&mut *tx)
             .await?;
         }
@@ -309,25 +600,35 @@ impl PluginService {
         // Create version record
         sqlx::query(
             r#"
-            INSERT INTO plugin_versions (plugin_id, version, changelog, file_path, file_size, file_hash)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO plugin_versions (plugin_id, version, changelog, file_path, file_size, file_hash,
+                                         file_format, normalized_file_path, canonical_hash,
+                                         default_locale, readme)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
             "#
         )
         .bind(&plugin_info.id)
         .bind(&plugin_info.version)
         .bind("")
-        .bind(&file_path)
-        .bind(file_size as i64)
-        .bind(&file_hash)
+        .bind(&stored.file_path)
+        .bind(stored.file_size)
+        .bind(&stored.file_hash)
+        .bind(inspected.format.extension())
+        .bind(&stored.canonical_file_path)
+        .bind(&stored.canonical_hash)
+        .bind(plugin_info.default_locale.as_deref().map(i18n::canonical_locale))
+        .bind(readme(&inspected.package, None))
         .execute(&mut *tx)
         .await?;
 
+        Self::insert_files(&mut tx, &plugin_info.id, &plugin_info.version, None, &stored.files).await?;
+
         // Save scripts
         for script in &plugin_info.scripts {
             sqlx::query(
                 r#"
-                INSERT INTO plugin_scripts (plugin_id, version, script_name, script_file, description, is_executable)
-                VALUES ($1, $2, $3, $4, $5, $6)
+                INSERT INTO plugin_scripts (plugin_id, version, script_name, script_file, description, is_executable,
+                                            parameters, exit_codes, output_formats, content, ngrams)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
                 "#
             )
             .bind(&plugin_info.id)
@@ -336,6 +637,11 @@ impl PluginService {
             .bind(&script.file)
             .bind(&script.description)
             .bind(script.executable)
+            .bind(Json(&script.parameters))
+            .bind(Json(&script.exit_codes))
+            .bind(&script.output_formats)
+            .bind(script_source(&inspected.package, &script.file))
+            .bind(script_ngrams(&script.name, script.description.as_deref()))
             .execute(&mut *tx)
             .await?;
         }
@@ -356,127 +662,1489 @@ impl PluginService {
             .await?;
         }
 
-        // Save dependencies
+        // Save dependencies, conflicts and provided capabilities for this version
         for dep in &plugin_info.dependencies {
             sqlx::query(
-                "INSERT INTO plugin_dependencies (plugin_id, dependency_id, min_version) VALUES ($1, $2, $3)"
+                r#"
+                INSERT INTO plugin_dependencies (plugin_id, version, dependency_id, min_version, optional, reason)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#
             )
             .bind(&plugin_info.id)
+            .bind(&plugin_info.version)
             .bind(&dep.id)
             .bind(&dep.min_version)
+            .bind(dep.optional)
+            .bind(&dep.reason)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for conflict in &plugin_info.conflicts {
+            sqlx::query(
+                "INSERT INTO plugin_conflicts (plugin_id, version, conflict_id, version_range) VALUES ($1, $2, $3, $4)"
+            )
+            .bind(&plugin_info.id)
+            .bind(&plugin_info.version)
+            .bind(&conflict.id)
+            .bind(&conflict.versions)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for capability in &plugin_info.provides {
+            sqlx::query("INSERT INTO plugin_provides (plugin_id, version, capability) VALUES ($1, $2, $3)")
+                .bind(&plugin_info.id)
+                .bind(&plugin_info.version)
+                .bind(capability)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // Save supported platforms and required commands for this version
+        for platform in &plugin_info.platforms {
+            sqlx::query(
+                "INSERT INTO plugin_platforms (plugin_id, version, os, arch) VALUES ($1, $2, $3, $4) ON CONFLICT DO NOTHING"
+            )
+            .bind(&plugin_info.id)
+            .bind(&plugin_info.version)
+            .bind(&platform.os)
+            .bind(&platform.arch)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for command in &plugin_info.requires {
+            sqlx::query(
+                "INSERT INTO plugin_requirements (plugin_id, version, command) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING"
+            )
+            .bind(&plugin_info.id)
+            .bind(&plugin_info.version)
+            .bind(command)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let permissions = &plugin_info.permissions;
+        if !permissions.is_empty() {
+            let (writable, readable): (Vec<_>, Vec<_>) = permissions.filesystem.iter().partition(|fs| fs.write);
+            let paths = |list: Vec<&FilesystemPermission>| list.into_iter().map(|fs| fs.path.clone()).collect::<Vec<_>>();
+            sqlx::query(
+                r#"
+                INSERT INTO plugin_permissions (plugin_id, version, network, root, filesystem_read,
+                                                filesystem_write, services, env_vars)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#
+            )
+            .bind(&plugin_info.id)
+            .bind(&plugin_info.version)
+            .bind(permissions.network)
+            .bind(permissions.root)
+            .bind(paths(readable))
+            .bind(paths(writable))
+            .bind(&permissions.services)
+            .bind(&permissions.env)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for (hook, file) in plugin_info.hooks.declared() {
+            sqlx::query("INSERT INTO plugin_hooks (plugin_id, version, hook, script_file) VALUES ($1, $2, $3, $4)")
+                .bind(&plugin_info.id)
+                .bind(&plugin_info.version)
+                .bind(hook)
+                .bind(file)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // Positions count within each kind, so the icon and first screenshot are both 0
+        let mut screenshots = 0;
+        for image in &stored_media {
+            let position = match image.kind {
+                MediaKind::Icon => 0,
+                MediaKind::Screenshot => {
+                    screenshots += 1;
+                    screenshots - 1
+                }
+            };
+            Self::insert_media(&mut tx, &plugin_info.id, &plugin_info.version, position, image).await?;
+        }
+
+        for localization in localizations(&inspected) {
+            sqlx::query(
+                r#"
+                INSERT INTO plugin_localizations (plugin_id, version, locale, name, description, readme)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#
+            )
+            .bind(&plugin_info.id)
+            .bind(&plugin_info.version)
+            .bind(&localization.locale)
+            .bind(&localization.name)
+            .bind(&localization.description)
+            .bind(&localization.readme)
             .execute(&mut *tx)
             .await?;
         }
 
+        if let Some(config) = &plugin_info.config {
+            sqlx::query("INSERT INTO plugin_config_schemas (plugin_id, version, schema) VALUES ($1, $2, $3)")
+                .bind(&plugin_info.id)
+                .bind(&plugin_info.version)
+                .bind(Json(config))
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        Self::index_search_text(&mut tx, &plugin_info.id).await?;
+
         tx.commit().await?;
 
-        // Clean up temporary file
-        let _ = self.storage_service.cleanup_temporary_file(&temp_file).await;
+        self.delta_service
+            .schedule(plugin_info.id.clone(), plugin_info.version.clone());
 
         Ok(UploadResponse {
-            plugin_id: plugin_info.id,
-            version: plugin_info.version,
+            plugin_id: plugin_info.id.clone(),
+            version: plugin_info.version.clone(),
             upload_id: upload_id.to_string(),
         })
     }
 
-    async fn extract_and_validate_plugin(&self, file_path: &std::path::Path) -> anyhow::Result<CreatePluginRequest> {
-        let file = std::fs::File::open(file_path)?;
-        let decoder = GzDecoder::new(file);
-        let mut archive = Archive::new(decoder);
+    /// Store the archive as uploaded plus its canonical tar.gz, and hash every file in it.
+    async fn store_package(
+        &self,
+        data: Vec<u8>,
+        inspected: &InspectedPackage,
+        platform: Option<&PluginPlatform>,
+    ) -> Result<StoredPackage, UploadError> {
+        let plugin_id = &inspected.manifest.id;
+        let version = &inspected.manifest.version;
+
+        // Keep declared scripts and hooks runnable even when the source archive had no unix modes
+        let manifest = &inspected.manifest;
+        let runnable: Vec<String> = manifest
+            .scripts
+            .iter()
+            .filter(|s| s.executable)
+            .map(|s| s.file.clone())
+            .chain(manifest.hooks.declared().map(|(_, file)| file.to_string()))
+            .collect();
+
+        // Repacking and hashing a large package takes too long for the async runtime
+        let package = inspected.package.clone();
+        let (data, file_hash, canonical, canonical_hash, files) = tokio::task::spawn_blocking(move || {
+            let mut package = (*package).clone();
+            for file in &runnable {
+                let entry = package.script(file).filter(|e| !e.is_executable());
+                if let Some(path) = entry.map(|e| e.path.clone()) {
+                    package.set_executable(&path);
+                }
+            }
+            let canonical = package.to_canonical_tar_gz()?;
+            let file_hash = hex::encode(Sha256::digest(&data));
+            let canonical_hash = hex::encode(Sha256::digest(&canonical));
+            anyhow::Ok((data, file_hash, canonical, canonical_hash, package.file_hashes()))
+        })
+        .await
+        .map_err(|e| UploadError::Storage(e.to_string()))?
+        .map_err(|e| UploadError::Storage(e.to_string()))?;
+
+        let file_size = data.len() as i64;
+        let file_path = self.store_file(data, plugin_id, version, platform, inspected.format.extension()).await?;
+        let canonical_file_path = self
+            .store_file(canonical, plugin_id, version, platform, CANONICAL_EXTENSION)
+            .await?;
 
-        let mut info_json = None;
+        Ok(StoredPackage {
+            file_path,
+            file_hash,
+            file_size,
+            canonical_file_path,
+            canonical_hash,
+            files,
+        })
+    }
 
-        for entry_result in archive.entries()? {
-            let mut entry = entry_result?;
-            let path = entry.path()?;
-            
-            if path.file_name() == Some(std::ffi::OsStr::new("info.json")) {
-                let mut contents = String::new();
-                entry.read_to_string(&mut contents)?;
-                info_json = Some(contents);
-                break;
+    async fn store_file(
+        &self,
+        data: Vec<u8>,
+        plugin_id: &str,
+        version: &str,
+        platform: Option<&PluginPlatform>,
+        extension: &str,
+    ) -> Result<String, UploadError> {
+        let stored = match platform {
+            Some(platform) => {
+                self.storage_service
+                    .store_platform_file(data, plugin_id, version, &platform_label(platform), extension)
+                    .await
             }
+            None => {
+                self.storage_service
+                    .store_plugin_file(data, plugin_id, version, extension)
+                    .await
+            }
+        };
+        stored.map_err(|e| UploadError::Storage(e.to_string()))
+    }
+
+    /// Checks against existing data that decide whether a manifest can be published as a new version.
+    async fn check_publishable(&self, plugin_info: &CreatePluginRequest) -> sqlx::Result<Vec<ManifestIssue>> {
+        let mut issues = Vec::new();
+
+        let existing_version = sqlx::query_as::<_, PluginVersion>(
+            "SELECT * FROM plugin_versions WHERE plugin_id = $1 AND version = $2"
+        )
+        .bind(&plugin_info.id)
+        .bind(&plugin_info.version)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        if existing_version.is_some() {
+            issues.push(ManifestIssue::new(
+                "/version",
+                format!("Version {} already exists for plugin {}", plugin_info.version, plugin_info.id),
+            ));
         }
 
-        let info_content = info_json.ok_or_else(|| {
-            anyhow::anyhow!("Plugin package missing info.json file")
-        })?;
+        // Optional dependencies may point at plugins that are not published yet
+        for (i, dep) in plugin_info.dependencies.iter().enumerate().filter(|(_, dep)| !dep.optional) {
+            let exists = sqlx::query_scalar::<_, bool>(
+                r#"
+                SELECT EXISTS (SELECT 1 FROM plugins WHERE id = $1)
+                    OR EXISTS (
+                        SELECT 1 FROM plugin_provides pp
+                        JOIN plugins p ON pp.plugin_id = p.id AND pp.version = p.current_version
+                        WHERE pp.capability = $1 AND p.status = 'active'
+                    )
+                "#
+            )
+            .bind(&dep.id)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+            if !exists {
+                issues.push(ManifestIssue::new(
+                    format!("/dependencies/{}/id", i),
+                    format!("Unknown dependency: {} is neither a plugin nor a provided capability", dep.id),
+                ));
+            }
+        }
 
-        let plugin_info: CreatePluginRequest = serde_json::from_str(&info_content)?;
-        plugin_info.validate()?;
+        // A capability named like a plugin would make dependencies on that name ambiguous
+        for (i, capability) in plugin_info.provides.iter().enumerate() {
+            let taken = sqlx::query_scalar::<_, String>("SELECT id FROM plugins WHERE id = $1 AND id <> $2")
+                .bind(capability)
+                .bind(&plugin_info.id)
+                .fetch_optional(&self.db_pool)
+                .await?
+                .is_some();
+
+            if taken {
+                issues.push(ManifestIssue::new(
+                    format!("/provides/{}", i),
+                    format!("Capability {} is already the ID of another plugin", capability),
+                ));
+            }
+        }
 
-        Ok(plugin_info)
+        Ok(issues)
     }
 
-    fn calculate_file_hash(&self, data: &[u8]) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(data);
-        hex::encode(hasher.finalize())
-    }
+    /// Attach a platform specific archive to an already published version.
+    pub async fn upload_platform_artifact(
+        &self,
+        plugin_id: &str,
+        platform: &PluginPlatform,
+        data: Vec<u8>,
+    ) -> Result<PluginArtifactInfo, UploadError> {
+        let (data, inspected, issues) = inspect_package(data).await?;
+        let inspected = match inspected {
+            Some(inspected) if issues.is_empty() => inspected,
+            _ => return Err(UploadError::Invalid(issues)),
+        };
+        let plugin_info = &inspected.manifest;
 
-    async fn get_plugin_versions(&self, plugin_id: &str) -> sqlx::Result<Vec<PluginVersionInfo>> {
-        let rows = sqlx::query(
-            "SELECT version, changelog, file_size, created_at, downloads, is_stable FROM plugin_versions WHERE plugin_id = $1 ORDER BY created_at DESC"
+        if plugin_info.id != plugin_id {
+            return Err(UploadError::Invalid(vec![ManifestIssue::new(
+                "/id",
+                format!("Package is for plugin {}, not {}", plugin_info.id, plugin_id),
+            )]));
+        }
+
+        let version_exists = sqlx::query_scalar::<_, i32>(
+            "SELECT id FROM plugin_versions WHERE plugin_id = $1 AND version = $2"
         )
         .bind(plugin_id)
-        .fetch_all(&self.db_pool)
-        .await?;
+        .bind(&plugin_info.version)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .is_some();
+
+        if !version_exists {
+            return Err(UploadError::Invalid(vec![ManifestIssue::new(
+                "/version",
+                format!(
+                    "Version {} of plugin {} must be uploaded before its platform archives",
+                    plugin_info.version, plugin_id
+                ),
+            )]));
+        }
 
-        let mut versions = Vec::new();
-        for row in rows {
-            versions.push(PluginVersionInfo {
-                version: row.get("version"),
-                changelog: row.get("changelog"),
-                file_size: row.get("file_size"),
-                created_at: row.get("created_at"),
-                downloads: row.get("downloads"),
-                is_stable: row.get("is_stable"),
+        let declared = self.get_plugin_platforms(plugin_id, &plugin_info.version).await?;
+        let covered = declared.is_empty()
+            || declared.iter().any(|p| {
+                p.os == platform.os && (p.arch.is_none() || p.arch == platform.arch)
             });
+        if !covered {
+            return Err(UploadError::Invalid(vec![ManifestIssue::new(
+                "/platforms",
+                format!(
+                    "Version {} of plugin {} does not declare support for {}",
+                    plugin_info.version,
+                    plugin_id,
+                    platform_label(platform)
+                ),
+            )]));
         }
 
-        Ok(versions)
-    }
+        let stored = self.store_package(data, &inspected, Some(platform)).await?;
 
-    async fn get_plugin_scripts(&self, plugin_id: &str, version: &str) -> sqlx::Result<Vec<PluginScriptInfo>> {
-        let rows = sqlx::query(
-            "SELECT script_name, script_file, description, is_executable FROM plugin_scripts WHERE plugin_id = $1 AND version = $2"
+        let mut tx = self.db_pool.begin().await?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO plugin_artifacts (plugin_id, version, os, arch, file_path, file_size, file_hash,
+                                          file_format, normalized_file_path, canonical_hash)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (plugin_id, version, os, COALESCE(arch, ''))
+            DO UPDATE SET file_path = EXCLUDED.file_path, file_size = EXCLUDED.file_size,
+                          file_hash = EXCLUDED.file_hash, file_format = EXCLUDED.file_format,
+                          normalized_file_path = EXCLUDED.normalized_file_path,
+                          canonical_hash = EXCLUDED.canonical_hash, created_at = NOW()
+            RETURNING id, created_at
+            "#
         )
         .bind(plugin_id)
-        .bind(version)
-        .fetch_all(&self.db_pool)
+        .bind(&plugin_info.version)
+        .bind(&platform.os)
+        .bind(&platform.arch)
+        .bind(&stored.file_path)
+        .bind(stored.file_size)
+        .bind(&stored.file_hash)
+        .bind(inspected.format.extension())
+        .bind(&stored.canonical_file_path)
+        .bind(&stored.canonical_hash)
+        .fetch_one(&mut *tx)
         .await?;
+        let artifact_id: i32 = row.get("id");
 
-        let mut scripts = Vec::new();
-        for row in rows {
-            scripts.push(PluginScriptInfo {
-                name: row.get("script_name"),
-                file: row.get("script_file"),
+        // A re-upload replaces the artifact, so its file list is rebuilt from scratch
+        sqlx::query("DELETE FROM plugin_files WHERE artifact_id = $1")
+            .bind(artifact_id)
+            .execute(&mut *tx)
+            .await?;
+        Self::insert_files(&mut tx, plugin_id, &plugin_info.version, Some(artifact_id), &stored.files).await?;
+
+        tx.commit().await?;
+
+        Ok(PluginArtifactInfo {
+            os: platform.os.clone(),
+            arch: platform.arch.clone(),
+            file_format: inspected.format.extension().to_string(),
+            file_size: stored.file_size,
+            file_hash: stored.file_hash,
+            canonical_hash: Some(stored.canonical_hash),
+            created_at: row.get("created_at"),
+        })
+    }
+
+    /// Add an icon or screenshot to the current version. A new icon replaces the old one.
+    /// Returns `None` when the plugin does not exist.
+    pub async fn add_plugin_media(
+        &self,
+        plugin_id: &str,
+        kind: MediaKind,
+        data: Vec<u8>,
+    ) -> Result<Option<PluginImage>, UploadError> {
+        let Some(version) = sqlx::query_scalar::<_, String>(
+            "SELECT current_version FROM plugins WHERE id = $1 AND status = 'active'"
+        )
+        .bind(plugin_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let images = vec![(kind, kind.as_str().to_string(), data)];
+        let Some((_, image)) = process_images(images).await?.pop() else {
+            return Ok(None);
+        };
+        let stored = self.store_media(plugin_id, &version, kind, image).await?;
+
+        // Lock the plugin row so concurrent uploads see each other's screenshots
+        // when checking the limit and picking a position.
+        let mut tx = self.db_pool.begin().await?;
+        let locked = sqlx::query_scalar::<_, String>(
+            "SELECT id FROM plugins WHERE id = $1 AND status = 'active' FOR UPDATE"
+        )
+        .bind(plugin_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if locked.is_none() {
+            return Ok(None);
+        }
+
+        let position = match kind {
+            MediaKind::Icon => {
+                sqlx::query("DELETE FROM plugin_media WHERE plugin_id = $1 AND version = $2 AND kind = 'icon'")
+                    .bind(plugin_id)
+                    .bind(&version)
+                    .execute(&mut *tx)
+                    .await?;
+                0
+            }
+            MediaKind::Screenshot => {
+                let count = sqlx::query_scalar::<_, i64>(
+                    "SELECT COUNT(*) FROM plugin_media WHERE plugin_id = $1 AND version = $2 AND kind = 'screenshot'"
+                )
+                .bind(plugin_id)
+                .bind(&version)
+                .fetch_one(&mut *tx)
+                .await?;
+                if count as usize >= media::MAX_SCREENSHOTS {
+                    return Err(UploadError::Invalid(vec![ManifestIssue::new(
+                        "",
+                        format!("Maximum {} screenshots allowed", media::MAX_SCREENSHOTS),
+                    )]));
+                }
+                count as i32
+            }
+        };
+        let id = Self::insert_media(&mut tx, plugin_id, &version, position, &stored).await?;
+        tx.commit().await?;
+
+        Ok(Some(media_image(plugin_id, id, stored.format, stored.width as i32, stored.height as i32)))
+    }
+
+    /// Write an image's four variants, named after a hash of the re-encoded image.
+    async fn store_media(
+        &self,
+        plugin_id: &str,
+        version: &str,
+        kind: MediaKind,
+        image: ProcessedImage,
+    ) -> Result<StoredMedia, UploadError> {
+        let name = &self.calculate_file_hash(&image.full)[..16];
+        let variants = [
+            (image.full, format!("{}-full.{}", name, image.format)),
+            (image.full_webp, format!("{}-full.webp", name)),
+            (image.thumbnail, format!("{}-thumb.{}", name, image.format)),
+            (image.thumbnail_webp, format!("{}-thumb.webp", name)),
+        ];
+
+        let mut paths = Vec::with_capacity(variants.len());
+        for (data, filename) in variants {
+            let path = self
+                .storage_service
+                .store_media_file(data, plugin_id, version, &filename)
+                .await
+                .map_err(|e| UploadError::Storage(e.to_string()))?;
+            paths.push(path);
+        }
+        let [file_path, webp_path, thumbnail_path, thumbnail_webp_path] =
+            <[String; 4]>::try_from(paths).expect("one path per variant");
+
+        Ok(StoredMedia {
+            kind,
+            format: image.format,
+            width: image.width,
+            height: image.height,
+            file_path,
+            webp_path,
+            thumbnail_path,
+            thumbnail_webp_path,
+        })
+    }
+
+    async fn insert_media(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        plugin_id: &str,
+        version: &str,
+        position: i32,
+        image: &StoredMedia,
+    ) -> sqlx::Result<i32> {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO plugin_media (plugin_id, version, kind, position, format, width, height,
+                                      file_path, webp_path, thumbnail_path, thumbnail_webp_path)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING id
+            "#
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .bind(image.kind.as_str())
+        .bind(position)
+        .bind(image.format)
+        .bind(image.width as i32)
+        .bind(image.height as i32)
+        .bind(&image.file_path)
+        .bind(&image.webp_path)
+        .bind(&image.thumbnail_path)
+        .bind(&image.thumbnail_webp_path)
+        .fetch_one(&mut **tx)
+        .await
+    }
+
+    async fn insert_files(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        plugin_id: &str,
+        version: &str,
+        artifact_id: Option<i32>,
+        files: &[PluginFileInfo],
+    ) -> sqlx::Result<()> {
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        let hashes: Vec<&str> = files.iter().map(|f| f.sha256.as_str()).collect();
+        let sizes: Vec<i64> = files.iter().map(|f| f.size).collect();
+        let executables: Vec<bool> = files.iter().map(|f| f.executable).collect();
+
+        sqlx::query(
+            r#"
+            INSERT INTO plugin_files (plugin_id, version, artifact_id, path, sha256, size, is_executable)
+            SELECT $1, $2, $3, * FROM UNNEST($4::text[], $5::text[], $6::bigint[], $7::bool[])
+            "#
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .bind(artifact_id)
+        .bind(&paths)
+        .bind(&hashes)
+        .bind(&sizes)
+        .bind(&executables)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    fn calculate_file_hash(&self, data: &[u8]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(data);
+        hex::encode(hasher.finalize())
+    }
+
+    async fn get_plugin_versions(&self, plugin_id: &str) -> sqlx::Result<Vec<PluginVersionInfo>> {
+        let rows = sqlx::query(
+            "SELECT version, changelog, file_size, file_format, canonical_hash, created_at, downloads, is_stable FROM plugin_versions WHERE plugin_id = $1 ORDER BY created_at DESC"
+        )
+        .bind(plugin_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let artifact_rows = sqlx::query(
+            "SELECT version, os, arch, file_format, file_size, file_hash, canonical_hash, created_at FROM plugin_artifacts WHERE plugin_id = $1 ORDER BY os, arch"
+        )
+        .bind(plugin_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut artifacts: HashMap<String, Vec<PluginArtifactInfo>> = HashMap::new();
+        for row in artifact_rows {
+            artifacts
+                .entry(row.get("version"))
+                .or_default()
+                .push(PluginArtifactInfo {
+                    os: row.get("os"),
+                    arch: row.get("arch"),
+                    file_format: row.get("file_format"),
+                    file_size: row.get("file_size"),
+                    file_hash: row.get("file_hash"),
+                    canonical_hash: row.get("canonical_hash"),
+                    created_at: row.get("created_at"),
+                });
+        }
+
+        let config_schemas: HashMap<String, PluginConfigSchema> = sqlx::query_as::<_, (String, Json<PluginConfigSchema>)>(
+            "SELECT version, schema FROM plugin_config_schemas WHERE plugin_id = $1"
+        )
+        .bind(plugin_id)
+        .fetch_all(&self.db_pool)
+        .await?
+        .into_iter()
+        .map(|(version, schema)| (version, schema.0))
+        .collect();
+
+        // Versions are newest first, so each one is compared with the row after it
+        let version_names: Vec<String> = rows.iter().map(|row| row.get("version")).collect();
+        let mut breaking: HashMap<String, Vec<ConfigBreakingChange>> = HashMap::new();
+        for pair in version_names.windows(2) {
+            let (newer, older) = (&pair[0], &pair[1]);
+            let empty = PluginConfigSchema::default();
+            let changes = plugin_config::breaking_changes(
+                config_schemas.get(older).unwrap_or(&empty),
+                config_schemas.get(newer).unwrap_or(&empty),
+            );
+            breaking.insert(newer.clone(), changes);
+        }
+
+        let mut versions = Vec::new();
+        for row in rows {
+            let version: String = row.get("version");
+            let version_artifacts = artifacts.remove(&version).unwrap_or_default();
+            let config_breaking_changes = breaking.remove(&version).unwrap_or_default();
+            versions.push(PluginVersionInfo {
+                version,
+                changelog: row.get("changelog"),
+                file_size: row.get("file_size"),
+                file_format: row.get("file_format"),
+                canonical_hash: row.get("canonical_hash"),
+                created_at: row.get("created_at"),
+                downloads: row.get("downloads"),
+                is_stable: row.get("is_stable"),
+                config_breaking_changes,
+                artifacts: version_artifacts,
+            });
+        }
+
+        Ok(versions)
+    }
+
+    async fn get_plugin_scripts(&self, plugin_id: &str, version: &str) -> sqlx::Result<Vec<PluginScriptInfo>> {
+        let rows = sqlx::query(
+            "SELECT script_name, script_file, description, is_executable, parameters, exit_codes, output_formats FROM plugin_scripts WHERE plugin_id = $1 AND version = $2 ORDER BY id"
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut scripts = Vec::new();
+        for row in rows {
+            scripts.push(PluginScriptInfo {
+                name: row.get("script_name"),
+                file: row.get("script_file"),
                 description: row.get("description"),
                 executable: row.get("is_executable"),
+                parameters: row.get::<Json<_>, _>("parameters").0,
+                exit_codes: row.get::<Json<_>, _>("exit_codes").0,
+                output_formats: row.get("output_formats"),
             });
         }
 
-        Ok(scripts)
+        Ok(scripts)
+    }
+
+    async fn get_plugin_dependencies(&self, plugin_id: &str, version: &str) -> sqlx::Result<Vec<PluginDependencyInfo>> {
+        let rows = sqlx::query(
+            "SELECT dependency_id, min_version, optional, reason FROM plugin_dependencies WHERE plugin_id = $1 AND version = $2 ORDER BY id"
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut dependencies = Vec::new();
+        for row in rows {
+            dependencies.push(PluginDependencyInfo {
+                id: row.get("dependency_id"),
+                min_version: row.get("min_version"),
+                optional: row.get("optional"),
+                reason: row.get("reason"),
+            });
+        }
+
+        Ok(dependencies)
+    }
+
+    async fn get_plugin_conflicts(&self, plugin_id: &str, version: &str) -> sqlx::Result<Vec<PluginConflict>> {
+        let rows = sqlx::query_as::<_, (String, Option<String>)>(
+            "SELECT conflict_id, version_range FROM plugin_conflicts WHERE plugin_id = $1 AND version = $2 ORDER BY id"
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, versions)| PluginConflict { id, versions })
+            .collect())
+    }
+
+    async fn get_plugin_provides(&self, plugin_id: &str, version: &str) -> sqlx::Result<Vec<String>> {
+        sqlx::query_scalar::<_, String>(
+            "SELECT capability FROM plugin_provides WHERE plugin_id = $1 AND version = $2 ORDER BY capability"
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_all(&self.db_pool)
+        .await
+    }
+
+    async fn get_plugin_platforms(&self, plugin_id: &str, version: &str) -> sqlx::Result<Vec<PluginPlatform>> {
+        let rows = sqlx::query(
+            "SELECT os, arch FROM plugin_platforms WHERE plugin_id = $1 AND version = $2 ORDER BY id"
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| PluginPlatform {
+                os: row.get("os"),
+                arch: row.get("arch"),
+            })
+            .collect())
+    }
+
+    async fn get_plugin_requirements(&self, plugin_id: &str, version: &str) -> sqlx::Result<Vec<String>> {
+        sqlx::query_scalar::<_, String>(
+            "SELECT command FROM plugin_requirements WHERE plugin_id = $1 AND version = $2 ORDER BY command"
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_all(&self.db_pool)
+        .await
+    }
+
+    async fn get_plugin_hooks(&self, plugin_id: &str, version: &str) -> sqlx::Result<PluginHooks> {
+        let rows = sqlx::query_as::<_, (String, String)>(
+            "SELECT hook, script_file FROM plugin_hooks WHERE plugin_id = $1 AND version = $2"
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut hooks = PluginHooks::default();
+        for (hook, file) in rows {
+            hooks.set(&hook, file);
+        }
+        Ok(hooks)
+    }
+
+    /// A version's default locale and README, and its localized variants.
+    async fn get_plugin_localizations(
+        &self,
+        plugin_id: &str,
+        version: &str,
+    ) -> sqlx::Result<(Option<String>, Option<String>, Vec<Localization>)> {
+        let (default_locale, readme) = sqlx::query_as::<_, (Option<String>, Option<String>)>(
+            "SELECT default_locale, readme FROM plugin_versions WHERE plugin_id = $1 AND version = $2"
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .unwrap_or_default();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT locale, name, description, readme FROM plugin_localizations
+            WHERE plugin_id = $1 AND version = $2
+            ORDER BY locale
+            "#
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let localizations = rows
+            .into_iter()
+            .map(|row| Localization {
+                locale: row.get("locale"),
+                name: row.get("name"),
+                description: row.get("description"),
+                readme: row.get("readme"),
+            })
+            .collect();
+
+        Ok((default_locale, readme, localizations))
+    }
+
+    /// Show list entries in the best match for `languages`. Only the current version's
+    /// localizations are used, and READMEs are left out.
+    async fn localize_summaries(&self, plugins: &mut [PluginSummary], languages: &[String]) -> sqlx::Result<()> {
+        if plugins.is_empty() || languages.is_empty() {
+            return Ok(());
+        }
+
+        let ids: Vec<String> = plugins.iter().map(|p| p.id.clone()).collect();
+        let mut localized = self.get_current_localizations(&ids).await?;
+        for plugin in plugins {
+            let Some((default_locale, localizations)) = localized.remove(&plugin.id) else {
+                continue;
+            };
+            if let Some(localization) = pick_localization(languages, default_locale.as_deref(), &localizations) {
+                if let Some(name) = &localization.name {
+                    plugin.name = name.clone();
+                }
+                if let Some(description) = &localization.description {
+                    plugin.description = Some(description.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Default locale and localized names and descriptions of each plugin's current version.
+    async fn get_current_localizations(
+        &self,
+        plugin_ids: &[String],
+    ) -> sqlx::Result<HashMap<String, (Option<String>, Vec<Localization>)>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT p.id, pv.default_locale, l.locale, l.name, l.description
+            FROM plugins p
+            JOIN plugin_versions pv ON pv.plugin_id = p.id AND pv.version = p.current_version
+            LEFT JOIN plugin_localizations l ON l.plugin_id = p.id AND l.version = p.current_version
+            WHERE p.id = ANY($1)
+            ORDER BY p.id, l.locale
+            "#
+        )
+        .bind(plugin_ids)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut localized: HashMap<String, (Option<String>, Vec<Localization>)> = HashMap::new();
+        for row in rows {
+            let entry = localized
+                .entry(row.get("id"))
+                .or_insert_with(|| (row.get("default_locale"), Vec::new()));
+            if let Some(locale) = row.get::<Option<String>, _>("locale") {
+                entry.1.push(Localization {
+                    locale,
+                    name: row.get("name"),
+                    description: row.get("description"),
+                    readme: None,
+                });
+            }
+        }
+        Ok(localized)
+    }
+
+    /// Store what Postgres cannot derive itself for the current version of a plugin: CJK
+    /// n-grams of its names and descriptions, and the pinyin of its Chinese names.
+    async fn index_search_text(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        plugin_id: &str,
+    ) -> sqlx::Result<()> {
+        let (name, description) = sqlx::query_as::<_, (String, Option<String>)>(
+            "SELECT name, description FROM plugins WHERE id = $1"
+        )
+        .bind(plugin_id)
+        .fetch_one(&mut **tx)
+        .await?;
+        let localized = sqlx::query_as::<_, (Option<String>, Option<String>)>(
+            r#"
+            SELECT l.name, l.description FROM plugins p
+            JOIN plugin_localizations l ON l.plugin_id = p.id AND l.version = p.current_version
+            WHERE p.id = $1
+            "#
+        )
+        .bind(plugin_id)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        let mut names: Vec<&str> = vec![&name];
+        names.extend(localized.iter().filter_map(|(name, _)| name.as_deref()));
+        names.sort();
+        names.dedup();
+        let mut descriptions: Vec<&str> = description.as_deref().into_iter().collect();
+        descriptions.extend(localized.iter().filter_map(|(_, description)| description.as_deref()));
+
+        sqlx::query("UPDATE plugins SET name_ngrams = $2, description_ngrams = $3 WHERE id = $1")
+            .bind(plugin_id)
+            .bind(search::cjk_ngrams(&names.join(" ")))
+            .bind(search::cjk_ngrams(&descriptions.join(" ")))
+            .execute(&mut **tx)
+            .await?;
+
+        sqlx::query("DELETE FROM plugin_name_pinyin WHERE plugin_id = $1")
+            .bind(plugin_id)
+            .execute(&mut **tx)
+            .await?;
+        for name in names {
+            if let Some((pinyin, initials)) = search::pinyin_keys(name) {
+                sqlx::query("INSERT INTO plugin_name_pinyin (plugin_id, name, pinyin, initials) VALUES ($1, $2, $3, $4)")
+                    .bind(plugin_id)
+                    .bind(name)
+                    .bind(pinyin)
+                    .bind(initials)
+                    .execute(&mut **tx)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Recompute popularity scores, related plugins and the spelling vocabulary now and then every
+    /// `SCORE_REFRESH_INTERVAL` in the background; failures are only logged, the previous
+    /// results stay in place.
+    pub fn schedule_score_refresh(self: &Arc<Self>) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SCORE_REFRESH_INTERVAL);
+            loop {
+                interval.tick().await;
+                match service.refresh_popularity_scores().await {
+                    Ok(updated) => tracing::debug!("Refreshed popularity scores of {} plugin(s)", updated),
+                    Err(e) => tracing::warn!("Failed to refresh popularity scores: {}", e),
+                }
+                match service.refresh_related_plugins().await {
+                    Ok(pairs) => tracing::debug!("Refreshed {} related plugin pair(s)", pairs),
+                    Err(e) => tracing::warn!("Failed to refresh related plugins: {}", e),
+                }
+                if let Err(e) = service.refresh_search_vocabulary().await {
+                    tracing::warn!("Failed to refresh search vocabulary: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Rebuild the words that `did_you_mean` corrects to from the current plugins.
+    pub async fn refresh_search_vocabulary(&self) -> sqlx::Result<()> {
+        sqlx::query("REFRESH MATERIALIZED VIEW CONCURRENTLY search_vocabulary")
+            .execute(&self.db_pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Fold daily downloads into `trending_score` and `hot_score`. Returns how many plugins'
+    /// scores changed.
+    pub async fn refresh_popularity_scores(&self) -> sqlx::Result<u64> {
+        let result = sqlx::query(
+            r#"
+            WITH scores AS (
+                SELECT p.id,
+                       coalesce(sum(d.downloads * power(0.5, (CURRENT_DATE - d.day) / $1::numeric)), 0)::float8 AS trending,
+                       coalesce(sum(d.downloads) FILTER (WHERE d.day > CURRENT_DATE - $2::int), 0)::float8 AS hot
+                FROM plugins p
+                LEFT JOIN plugin_daily_downloads d ON d.plugin_id = p.id AND d.day > CURRENT_DATE - $3::int
+                GROUP BY p.id
+            )
+            UPDATE plugins p SET trending_score = s.trending, hot_score = s.hot
+            FROM scores s
+            WHERE p.id = s.id AND (p.trending_score <> s.trending OR p.hot_score <> s.hot)
+            "#
+        )
+        .bind(TRENDING_HALF_LIFE_DAYS)
+        .bind(HOT_WINDOW_DAYS)
+        .bind(TRENDING_WINDOW_DAYS)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Recompute every active plugin's best `RELATED_PLUGINS` matches. Plugins are scored by
+    /// tag overlap (Jaccard), a shared author and overlap of their dependencies. Returns how
+    /// many pairs were stored.
+    pub async fn refresh_related_plugins(&self) -> sqlx::Result<u64> {
+        let mut tx = self.db_pool.begin().await?;
+
+        sqlx::query("DELETE FROM plugin_related").execute(&mut *tx).await?;
+
+        let result = sqlx::query(
+            r#"
+            WITH active AS (
+                SELECT id, lower(author) AS author, current_version FROM plugins WHERE status = 'active'
+            ),
+            tags AS (
+                SELECT DISTINCT t.plugin_id, lower(t.tag) AS tag FROM plugin_tags t JOIN active a ON a.id = t.plugin_id
+            ),
+            deps AS (
+                SELECT DISTINCT d.plugin_id, d.dependency_id FROM plugin_dependencies d
+                JOIN active a ON a.id = d.plugin_id AND d.version = a.current_version
+            ),
+            tag_counts AS (SELECT plugin_id, COUNT(*) AS n FROM tags GROUP BY plugin_id),
+            dep_counts AS (SELECT plugin_id, COUNT(*) AS n FROM deps GROUP BY plugin_id),
+            tag_pairs AS (
+                SELECT x.plugin_id, y.plugin_id AS related_id, array_agg(x.tag ORDER BY x.tag) AS shared
+                FROM tags x JOIN tags y ON y.tag = x.tag AND y.plugin_id <> x.plugin_id
+                GROUP BY 1, 2
+            ),
+            dep_pairs AS (
+                SELECT x.plugin_id, y.plugin_id AS related_id, array_agg(x.dependency_id ORDER BY x.dependency_id) AS shared
+                FROM deps x JOIN deps y ON y.dependency_id = x.dependency_id AND y.plugin_id <> x.plugin_id
+                GROUP BY 1, 2
+            ),
+            author_pairs AS (
+                SELECT x.id AS plugin_id, y.id AS related_id
+                FROM active x JOIN active y ON y.author = x.author AND y.id <> x.id
+            ),
+            pairs AS (
+                SELECT plugin_id, related_id FROM tag_pairs
+                UNION SELECT plugin_id, related_id FROM dep_pairs
+                UNION SELECT plugin_id, related_id FROM author_pairs
+            ),
+            scored AS (
+                SELECT pr.plugin_id, pr.related_id,
+                       coalesce(tp.shared, '{}') AS shared_tags,
+                       ap.plugin_id IS NOT NULL AS same_author,
+                       coalesce(dp.shared, '{}') AS shared_dependencies,
+                       $1::float8 * coalesce(cardinality(tp.shared)::float8 / (tx.n + ty.n - cardinality(tp.shared)), 0)
+                           + CASE WHEN ap.plugin_id IS NULL THEN 0 ELSE $2::float8 END
+                           + $3::float8 * coalesce(cardinality(dp.shared)::float8 / (dx.n + dy.n - cardinality(dp.shared)), 0)
+                           AS score
+                FROM pairs pr
+                LEFT JOIN tag_pairs tp ON tp.plugin_id = pr.plugin_id AND tp.related_id = pr.related_id
+                LEFT JOIN dep_pairs dp ON dp.plugin_id = pr.plugin_id AND dp.related_id = pr.related_id
+                LEFT JOIN author_pairs ap ON ap.plugin_id = pr.plugin_id AND ap.related_id = pr.related_id
+                LEFT JOIN tag_counts tx ON tx.plugin_id = pr.plugin_id
+                LEFT JOIN tag_counts ty ON ty.plugin_id = pr.related_id
+                LEFT JOIN dep_counts dx ON dx.plugin_id = pr.plugin_id
+                LEFT JOIN dep_counts dy ON dy.plugin_id = pr.related_id
+            ),
+            ranked AS (
+                SELECT *, row_number() OVER (PARTITION BY plugin_id ORDER BY score DESC, related_id) AS position
+                FROM scored
+            )
+            INSERT INTO plugin_related (plugin_id, related_id, position, score, shared_tags, same_author, shared_dependencies)
+            SELECT plugin_id, related_id, position, score, shared_tags, same_author, shared_dependencies
+            FROM ranked WHERE position <= $4
+            "#
+        )
+        .bind(RELATED_TAG_WEIGHT)
+        .bind(RELATED_AUTHOR_WEIGHT)
+        .bind(RELATED_DEPENDENCY_WEIGHT)
+        .bind(RELATED_PLUGINS)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Up to `limit` plugins related to `plugin_id`, best first, or `None` when the plugin
+    /// does not exist.
+    pub async fn get_related_plugins(
+        &self,
+        plugin_id: &str,
+        limit: i64,
+        languages: &[String],
+    ) -> sqlx::Result<Option<Vec<RelatedPlugin>>> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM plugins WHERE id = $1 AND status = 'active')")
+            .bind(plugin_id)
+            .fetch_one(&self.db_pool)
+            .await?;
+        if !exists {
+            return Ok(None);
+        }
+
+        let sql = format!(
+            "SELECT {}, r.score, r.shared_tags, r.same_author, r.shared_dependencies
+             FROM plugin_related r
+             JOIN plugins p ON p.id = r.related_id
+             WHERE r.plugin_id = $1 AND p.status = 'active'
+             ORDER BY r.position
+             LIMIT $2",
+            SUMMARY_COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(plugin_id)
+            .bind(limit)
+            .fetch_all(&self.db_pool)
+            .await?;
+
+        let mut plugins: Vec<PluginSummary> = rows.iter().map(summary_from_row).collect();
+        self.localize_summaries(&mut plugins, languages).await?;
+        self.attach_icons(&mut plugins).await?;
+
+        let related = plugins
+            .into_iter()
+            .zip(&rows)
+            .map(|(plugin, row)| RelatedPlugin {
+                plugin,
+                score: row.get("score"),
+                shared_tags: row.get("shared_tags"),
+                same_author: row.get("same_author"),
+                shared_dependencies: row.get("shared_dependencies"),
+            })
+            .collect();
+
+        Ok(Some(related))
+    }
+
+    /// Index plugins stored before CJK search existed. Returns how many were indexed.
+    pub async fn index_unindexed_plugins(&self) -> sqlx::Result<usize> {
+        let ids: Vec<String> = sqlx::query_scalar("SELECT id FROM plugins WHERE name_ngrams IS NULL")
+            .fetch_all(&self.db_pool)
+            .await?;
+        for id in &ids {
+            let mut tx = self.db_pool.begin().await?;
+            Self::index_search_text(&mut tx, id).await?;
+            tx.commit().await?;
+        }
+        Ok(ids.len())
+    }
+
+    /// Index the scripts of current versions stored before script search existed, reading
+    /// their source from the stored packages. Returns how many were indexed.
+    pub async fn index_unindexed_scripts(&self) -> sqlx::Result<usize> {
+        let rows = sqlx::query(
+            r#"
+            SELECT s.id, s.script_name, s.script_file, s.description,
+                   coalesce(pv.normalized_file_path, pv.file_path) AS package_path
+            FROM plugin_scripts s
+            JOIN plugins p ON p.id = s.plugin_id AND p.current_version = s.version
+            JOIN plugin_versions pv ON pv.plugin_id = s.plugin_id AND pv.version = s.version
+            WHERE s.ngrams IS NULL
+            ORDER BY package_path
+            "#
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut package: Option<(String, Option<PluginPackage>)> = None;
+        for row in &rows {
+            let path: String = row.get("package_path");
+            if package.as_ref().map(|(loaded, _)| loaded) != Some(&path) {
+                // A missing archive only leaves the source unindexed
+                let unpacked = match tokio::fs::read(&path).await {
+                    Ok(data) => PluginPackage::from_bytes(&data).ok().map(|(_, package)| package),
+                    Err(_) => None,
+                };
+                package = Some((path, unpacked));
+            }
+            let file: String = row.get("script_file");
+            let name: String = row.get("script_name");
+            let description: Option<String> = row.get("description");
+            let content = package
+                .as_ref()
+                .and_then(|(_, unpacked)| unpacked.as_ref())
+                .and_then(|unpacked| script_source(unpacked, &file));
+
+            sqlx::query("UPDATE plugin_scripts SET content = $2, ngrams = $3 WHERE id = $1")
+                .bind(row.get::<i32, _>("id"))
+                .bind(content)
+                .bind(script_ngrams(&name, description.as_deref()))
+                .execute(&self.db_pool)
+                .await?;
+        }
+        Ok(rows.len())
+    }
+
+    /// Scripts of current versions matching `query`, best first, in plugins matching
+    /// `filters`, with how many match in total.
+    pub async fn search_scripts(
+        &self,
+        query: &str,
+        filters: &PluginFilters,
+        limit: i32,
+        offset: i32,
+        languages: &[String],
+    ) -> sqlx::Result<(Vec<ScriptSearchResult>, i64)> {
+        let terms = search::search_terms(query);
+        let Some(tsquery) = search::tsquery(&terms) else {
+            return Ok((Vec::new(), 0));
+        };
+        let plugin_filters = PluginFilters { search: None, ..filters.clone() };
+        let mut filter = FilterSql::new(&plugin_filters, false);
+        let index = filter.bind(tsquery);
+        filter.push(format!("s.search_vector @@ ${}::tsquery", index));
+
+        let from = "FROM plugin_scripts s JOIN plugins p ON p.id = s.plugin_id AND s.version = p.current_version";
+        let count_sql = format!("SELECT COUNT(*) {} WHERE {}", from, filter.conditions);
+        let mut count_query = sqlx::query_scalar(&count_sql);
+        for value in &filter.binds {
+            count_query = count_query.bind(value);
+        }
+        let total: i64 = count_query.fetch_one(&self.db_pool).await?;
+
+        let sql = format!(
+            "SELECT {}, s.script_name, s.script_file, s.description AS script_description,
+                    coalesce(s.is_executable, false) AS is_executable, s.content
+             {}
+             WHERE {}
+             ORDER BY ts_rank(s.search_vector, ${}::tsquery) DESC, p.downloads DESC NULLS LAST, p.id, s.script_name
+             LIMIT ${} OFFSET ${}",
+            SUMMARY_COLUMNS,
+            from,
+            filter.conditions,
+            index,
+            filter.binds.len() + 1,
+            filter.binds.len() + 2
+        );
+        let mut search_query = sqlx::query(&sql);
+        for value in &filter.binds {
+            search_query = search_query.bind(value);
+        }
+        let rows = search_query
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(&self.db_pool)
+            .await?;
+
+        let mut plugins: Vec<PluginSummary> = rows.iter().map(summary_from_row).collect();
+        self.localize_summaries(&mut plugins, languages).await?;
+        self.attach_icons(&mut plugins).await?;
+
+        let scripts = plugins
+            .into_iter()
+            .zip(&rows)
+            .map(|(plugin, row)| {
+                let name: String = row.get("script_name");
+                let file: String = row.get("script_file");
+                let description: Option<String> = row.get("script_description");
+                let content: Option<String> = row.get("content");
+                ScriptSearchResult {
+                    highlights: ScriptHighlights {
+                        name: search::highlight(&name, &terms),
+                        file: search::highlight(&file, &terms),
+                        description: description
+                            .as_deref()
+                            .and_then(|d| search::highlight_fragment(d, &terms, HIGHLIGHT_WORDS)),
+                        snippet: content
+                            .as_deref()
+                            .and_then(|c| search::highlight_lines(c, &terms, SNIPPET_CONTEXT_LINES)),
+                    },
+                    name,
+                    file,
+                    description,
+                    executable: row.get("is_executable"),
+                    plugin,
+                }
+            })
+            .collect();
+
+        Ok((scripts, total))
+    }
+
+    /// The icon and screenshots of a version. Versions without an icon keep showing the
+    /// most recent one uploaded for the plugin.
+    async fn get_plugin_media(
+        &self,
+        plugin_id: &str,
+        version: &str,
+    ) -> sqlx::Result<(Option<PluginImage>, Vec<PluginImage>)> {
+        let icon = sqlx::query_as::<_, (i32, String, i32, i32)>(
+            r#"
+            SELECT id, format, width, height FROM plugin_media
+            WHERE plugin_id = $1 AND kind = 'icon'
+            ORDER BY version = $2 DESC, id DESC
+            LIMIT 1
+            "#
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        let screenshots = sqlx::query_as::<_, (i32, String, i32, i32)>(
+            r#"
+            SELECT id, format, width, height FROM plugin_media
+            WHERE plugin_id = $1 AND version = $2 AND kind = 'screenshot'
+            ORDER BY position, id
+            "#
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let to_image = |(id, format, width, height): (i32, String, i32, i32)| {
+            media_image(plugin_id, id, &format, width, height)
+        };
+        Ok((icon.map(to_image), screenshots.into_iter().map(to_image).collect()))
+    }
+
+    async fn attach_icons(&self, plugins: &mut [PluginSummary]) -> sqlx::Result<()> {
+        if plugins.is_empty() {
+            return Ok(());
+        }
+
+        let ids: Vec<String> = plugins.iter().map(|p| p.id.clone()).collect();
+        let rows = sqlx::query_as::<_, (String, i32, String, i32, i32)>(
+            r#"
+            SELECT DISTINCT ON (m.plugin_id) m.plugin_id, m.id, m.format, m.width, m.height
+            FROM plugin_media m
+            JOIN plugins p ON p.id = m.plugin_id
+            WHERE m.plugin_id = ANY($1) AND m.kind = 'icon'
+            ORDER BY m.plugin_id, m.version = p.current_version DESC, m.id DESC
+            "#
+        )
+        .bind(&ids)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut icons: HashMap<String, PluginImage> = rows
+            .into_iter()
+            .map(|(plugin_id, id, format, width, height)| {
+                let image = media_image(&plugin_id, id, &format, width, height);
+                (plugin_id, image)
+            })
+            .collect();
+        for plugin in plugins {
+            plugin.icon = icons.remove(&plugin.id);
+        }
+        Ok(())
+    }
+
+    /// The stored file and content type for one variant of an image, e.g. `thumb.webp`.
+    pub async fn get_media_file(
+        &self,
+        plugin_id: &str,
+        media_id: i32,
+        variant: &str,
+    ) -> sqlx::Result<Option<(String, &'static str)>> {
+        let Some(row) = sqlx::query(
+            r#"
+            SELECT format, file_path, webp_path, thumbnail_path, thumbnail_webp_path
+            FROM plugin_media WHERE id = $1 AND plugin_id = $2
+            "#
+        )
+        .bind(media_id)
+        .bind(plugin_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let format: String = row.get("format");
+        let content_type = if format == "jpg" { "image/jpeg" } else { "image/png" };
+        let (column, content_type) = match variant {
+            "full.webp" => ("webp_path", "image/webp"),
+            "thumb.webp" => ("thumbnail_webp_path", "image/webp"),
+            _ if variant == format!("full.{}", format) => ("file_path", content_type),
+            _ if variant == format!("thumb.{}", format) => ("thumbnail_path", content_type),
+            _ => return Ok(None),
+        };
+        Ok(Some((row.get(column), content_type)))
     }
 
-    async fn get_plugin_dependencies(&self, plugin_id: &str) -> sqlx::Result<Vec<PluginDependencyInfo>> {
-        let rows = sqlx::query(
-            "SELECT dependency_id, min_version FROM plugin_dependencies WHERE plugin_id = $1"
+    async fn get_plugin_config_schema(&self, plugin_id: &str, version: &str) -> sqlx::Result<Option<PluginConfigSchema>> {
+        let schema = sqlx::query_scalar::<_, Json<PluginConfigSchema>>(
+            "SELECT schema FROM plugin_config_schemas WHERE plugin_id = $1 AND version = $2"
         )
         .bind(plugin_id)
-        .fetch_all(&self.db_pool)
+        .bind(version)
+        .fetch_optional(&self.db_pool)
         .await?;
 
-        let mut dependencies = Vec::new();
-        for row in rows {
-            dependencies.push(PluginDependencyInfo {
-                id: row.get("dependency_id"),
-                min_version: row.get("min_version"),
+        Ok(schema.map(|schema| schema.0))
+    }
+
+    /// The config schema of a version (the current one by default) and the defaults it declares.
+    pub async fn get_plugin_config(
+        &self,
+        plugin_id: &str,
+        version: Option<&str>,
+    ) -> sqlx::Result<Option<PluginConfigResponse>> {
+        let version = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT pv.version FROM plugin_versions pv
+            JOIN plugins p ON pv.plugin_id = p.id
+            WHERE p.id = $1 AND pv.version = COALESCE($2, p.current_version)
+            "#
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        let Some(version) = version else {
+            return Ok(None);
+        };
+        let schema = self.get_plugin_config_schema(plugin_id, &version).await?;
+        let defaults = schema.as_ref().map(plugin_config::defaults).unwrap_or_default();
+
+        Ok(Some(PluginConfigResponse {
+            plugin_id: plugin_id.to_string(),
+            version,
+            schema,
+            defaults,
+        }))
+    }
+
+    async fn get_plugin_permissions(&self, plugin_id: &str, version: &str) -> sqlx::Result<PluginPermissions> {
+        let row = sqlx::query(
+            "SELECT network, root, filesystem_read, filesystem_write, services, env_vars FROM plugin_permissions WHERE plugin_id = $1 AND version = $2"
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(PluginPermissions::default());
+        };
+
+        let readable: Vec<String> = row.get("filesystem_read");
+        let writable: Vec<String> = row.get("filesystem_write");
+        let filesystem = readable
+            .into_iter()
+            .map(|path| FilesystemPermission { path, write: false })
+            .chain(writable.into_iter().map(|path| FilesystemPermission { path, write: true }))
+            .collect();
+
+        Ok(PluginPermissions {
+            network: row.get("network"),
+            root: row.get("root"),
+            filesystem,
+            services: row.get("services"),
+            env: row.get("env_vars"),
+        })
+    }
+
+    /// Updates available for the given installed plugins, with the permissions each update
+    /// adds over the installed version and the installed plugins it conflicts with.
+    /// Unknown installed versions are treated as granting nothing.
+    pub async fn check_updates(&self, installed: &[InstalledPlugin]) -> sqlx::Result<Vec<PluginUpdateInfo>> {
+        let ids: Vec<&str> = installed.iter().map(|p| p.id.as_str()).collect();
+        let current_versions: HashMap<String, String> = sqlx::query_as(
+            "SELECT id, current_version FROM plugins WHERE id = ANY($1) AND status = 'active'"
+        )
+        .bind(&ids)
+        .fetch_all(&self.db_pool)
+        .await?
+        .into_iter()
+        .collect();
+
+        let mut updates = Vec::new();
+        for plugin in installed {
+            let Some(latest) = current_versions.get(&plugin.id) else {
+                continue;
+            };
+            if compare_versions(latest, &plugin.version) != Ordering::Greater {
+                continue;
+            }
+
+            let latest_permissions = self.get_plugin_permissions(&plugin.id, latest).await?;
+            let installed_permissions = self.get_plugin_permissions(&plugin.id, &plugin.version).await?;
+            let new_permissions = latest_permissions.added_since(&installed_permissions);
+
+            let mut conflicts_with = Vec::new();
+            for conflict in self.get_plugin_conflicts(&plugin.id, latest).await? {
+                let range = conflict.versions.as_deref().and_then(|r| VersionRange::parse(r).ok());
+                conflicts_with.extend(
+                    installed
+                        .iter()
+                        .filter(|other| other.id == conflict.id)
+                        .filter(|other| range.as_ref().is_none_or(|range| range.matches(&other.version)))
+                        .cloned(),
+                );
+            }
+
+            updates.push(PluginUpdateInfo {
+                plugin_id: plugin.id.clone(),
+                installed_version: plugin.version.clone(),
+                latest_version: latest.clone(),
+                requires_consent: !new_permissions.is_empty(),
+                new_permissions,
+                conflicts_with,
             });
         }
 
-        Ok(dependencies)
+        Ok(updates)
     }
 
     async fn get_plugin_tags(&self, plugin_id: &str) -> sqlx::Result<Vec<String>> {
@@ -490,17 +2158,25 @@ impl PluginService {
         Ok(rows)
     }
 
-    pub async fn get_download_info(&self, plugin_id: &str, version: Option<&str>) -> sqlx::Result<Option<(String, String)>> {
+    /// Resolve the file to serve. Returns the path, the download filename and its format;
+    /// asking for `tar.gz` serves the converted archive when the upload used another format.
+    pub async fn get_download_info(
+        &self,
+        plugin_id: &str,
+        version: Option<&str>,
+        platform: Option<&PluginPlatform>,
+        format: Option<PackageFormat>,
+    ) -> sqlx::Result<Option<(String, String, PackageFormat)>> {
         let query = if let Some(v) = version {
             sqlx::query(
-                "SELECT file_path, version FROM plugin_versions WHERE plugin_id = $1 AND version = $2"
+                "SELECT file_path, file_format, normalized_file_path, version FROM plugin_versions WHERE plugin_id = $1 AND version = $2"
             )
             .bind(plugin_id)
             .bind(v)
         } else {
             sqlx::query(
                 r#"
-                SELECT pv.file_path, pv.version 
+                SELECT pv.file_path, pv.file_format, pv.normalized_file_path, pv.version 
                 FROM plugin_versions pv 
                 JOIN plugins p ON pv.plugin_id = p.id 
                 WHERE p.id = $1 AND pv.version = p.current_version
@@ -509,14 +2185,196 @@ impl PluginService {
             .bind(plugin_id)
         };
 
-        if let Some(row) = query.fetch_optional(&self.db_pool).await? {
-            let file_path: String = row.get("file_path");
-            let version: String = row.get("version");
-            let filename = format!("{}-{}.tar.gz", plugin_id, version);
-            Ok(Some((file_path, filename)))
-        } else {
-            Ok(None)
+        let Some(mut row) = query.fetch_optional(&self.db_pool).await? else {
+            return Ok(None);
+        };
+
+        let version: String = row.get("version");
+        let mut label = None;
+
+        // Prefer an exact os/arch archive, then one for any arch of the os, then the generic one
+        if let Some(platform) = platform {
+            let artifact = sqlx::query(
+                r#"
+                SELECT file_path, file_format, normalized_file_path, os, arch FROM plugin_artifacts
+                WHERE plugin_id = $1 AND version = $2 AND os = $3
+                  AND (arch IS NULL OR arch = $4)
+                ORDER BY arch NULLS LAST
+                LIMIT 1
+                "#
+            )
+            .bind(plugin_id)
+            .bind(&version)
+            .bind(&platform.os)
+            .bind(&platform.arch)
+            .fetch_optional(&self.db_pool)
+            .await?;
+
+            if let Some(artifact) = artifact {
+                let matched = PluginPlatform {
+                    os: artifact.get("os"),
+                    arch: artifact.get("arch"),
+                };
+                label = Some(platform_label(&matched));
+                row = artifact;
+            }
         }
+
+        let stored_format: String = row.get("file_format");
+        let stored_format = PackageFormat::from_extension(&stored_format).unwrap_or(PackageFormat::TarGz);
+        let normalized_file_path: Option<String> = row.get("normalized_file_path");
+
+        let (file_path, served_format) = match (format, normalized_file_path) {
+            (Some(PackageFormat::TarGz), Some(normalized)) => (normalized, PackageFormat::TarGz),
+            _ => (row.get("file_path"), stored_format),
+        };
+
+        let filename = match label {
+            Some(label) => format!("{}-{}-{}.{}", plugin_id, version, label, served_format.extension()),
+            None => format!("{}-{}.{}", plugin_id, version, served_format.extension()),
+        };
+        Ok(Some((file_path, filename, served_format)))
+    }
+
+    /// Compare the generic archives of two versions file by file, plus their info.json.
+    /// Returns `None` when either version or its archive does not exist.
+    pub async fn get_plugin_diff(
+        &self,
+        plugin_id: &str,
+        from_version: &str,
+        to_version: &str,
+    ) -> anyhow::Result<Option<PluginDiffResponse>> {
+        let (Some(old), Some(new)) = (
+            self.load_version_package(plugin_id, from_version).await?,
+            self.load_version_package(plugin_id, to_version).await?,
+        ) else {
+            return Ok(None);
+        };
+
+        let manifest_of = |package: &PluginPackage| -> serde_json::Value {
+            package
+                .info_json()
+                .and_then(|entry| serde_json::from_slice(&entry.data).ok())
+                .unwrap_or(serde_json::Value::Null)
+        };
+        let manifest_changes = diff::diff_manifests(&manifest_of(&old), &manifest_of(&new));
+        let files = diff::diff_packages(&old, &new);
+
+        Ok(Some(PluginDiffResponse {
+            plugin_id: plugin_id.to_string(),
+            from_version: from_version.to_string(),
+            to_version: to_version.to_string(),
+            added: files.added,
+            removed: files.removed,
+            modified: files.modified,
+            manifest_changes,
+        }))
+    }
+
+    async fn load_version_package(&self, plugin_id: &str, version: &str) -> anyhow::Result<Option<PluginPackage>> {
+        let file_path: Option<String> = sqlx::query_scalar(
+            "SELECT COALESCE(normalized_file_path, file_path) FROM plugin_versions WHERE plugin_id = $1 AND version = $2"
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        let Some(file_path) = file_path else {
+            return Ok(None);
+        };
+        let data = match tokio::fs::read(&file_path).await {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let (_, package) = PluginPackage::from_bytes(&data)?;
+        Ok(Some(package))
+    }
+
+    /// Per-file hashes of a version's canonical archive, for the archive `get_download_info` would pick.
+    pub async fn get_plugin_files(
+        &self,
+        plugin_id: &str,
+        version: Option<&str>,
+        platform: Option<&PluginPlatform>,
+    ) -> sqlx::Result<Option<PluginFilesResponse>> {
+        let row = sqlx::query(
+            r#"
+            SELECT pv.version, pv.canonical_hash
+            FROM plugin_versions pv
+            JOIN plugins p ON pv.plugin_id = p.id
+            WHERE p.id = $1 AND pv.version = COALESCE($2, p.current_version)
+            "#
+        )
+        .bind(plugin_id)
+        .bind(version)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let version: String = row.get("version");
+        let mut canonical_hash: Option<String> = row.get("canonical_hash");
+        let mut artifact_id: Option<i32> = None;
+        let mut matched = None;
+
+        if let Some(platform) = platform {
+            let artifact = sqlx::query(
+                r#"
+                SELECT id, os, arch, canonical_hash FROM plugin_artifacts
+                WHERE plugin_id = $1 AND version = $2 AND os = $3
+                  AND (arch IS NULL OR arch = $4)
+                ORDER BY arch NULLS LAST
+                LIMIT 1
+                "#
+            )
+            .bind(plugin_id)
+            .bind(&version)
+            .bind(&platform.os)
+            .bind(&platform.arch)
+            .fetch_optional(&self.db_pool)
+            .await?;
+
+            if let Some(artifact) = artifact {
+                artifact_id = Some(artifact.get("id"));
+                canonical_hash = artifact.get("canonical_hash");
+                matched = Some(PluginPlatform {
+                    os: artifact.get("os"),
+                    arch: artifact.get("arch"),
+                });
+            }
+        }
+
+        let files = sqlx::query(
+            r#"
+            SELECT path, sha256, size, is_executable FROM plugin_files
+            WHERE plugin_id = $1 AND version = $2 AND artifact_id IS NOT DISTINCT FROM $3
+            ORDER BY path
+            "#
+        )
+        .bind(plugin_id)
+        .bind(&version)
+        .bind(artifact_id)
+        .fetch_all(&self.db_pool)
+        .await?
+        .into_iter()
+        .map(|row| PluginFileInfo {
+            path: row.get("path"),
+            sha256: row.get("sha256"),
+            size: row.get("size"),
+            executable: row.get("is_executable"),
+        })
+        .collect();
+
+        Ok(Some(PluginFilesResponse {
+            plugin_id: plugin_id.to_string(),
+            version,
+            platform: matched,
+            canonical_hash,
+            files,
+        }))
     }
 
     pub async fn increment_download_count(&self, plugin_id: &str, version: Option<&str>) -> sqlx::Result<()> {
@@ -539,6 +2397,16 @@ impl PluginService {
         .execute(&mut *tx)
         .await?;
 
+        sqlx::query(
+            r#"
+            INSERT INTO plugin_daily_downloads (plugin_id, day, downloads) VALUES ($1, CURRENT_DATE, 1)
+            ON CONFLICT (plugin_id, day) DO UPDATE SET downloads = plugin_daily_downloads.downloads + 1
+            "#
+        )
+        .bind(plugin_id)
+        .execute(&mut *tx)
+        .await?;
+
         tx.commit().await?;
 
         Ok(())
@@ -644,19 +2512,602 @@ impl PluginService {
         })
     }
 
-    pub async fn get_search_suggestions(&self, query: &str) -> sqlx::Result<Vec<String>> {
-        let suggestions = sqlx::query_scalar::<_, String>(
+    /// A corrected query for a search that found little: each word that is not part of a
+    /// plugin name, ID or tag is replaced by the closest one, preferring popular plugins.
+    /// Words come from `search_vocabulary`, so plugins published since the last refresh are
+    /// not known yet. `None` when nothing needs correcting or nothing is close.
+    pub async fn did_you_mean(&self, query: &str) -> sqlx::Result<Option<String>> {
+        let terms = search::search_terms(query);
+        if terms.is_empty() || !terms.iter().all(|term| term.is_ascii()) {
+            return Ok(None);
+        }
+        let words: Vec<&str> = terms.iter().map(String::as_str).filter(|term| term.len() >= 3).collect();
+        let prefixes: Vec<String> = words.iter().map(|word| format!("{}%", search::escape_like(word))).collect();
+
+        // `%` keeps words at least DID_YOU_MEAN_SIMILARITY alike, found through the trigram index
+        let mut tx = self.begin_search(true).await?;
+        let corrections = sqlx::query_as::<_, (String, String)>(
+            r#"
+            SELECT DISTINCT ON (q.word) q.word, v.word
+            FROM unnest($1::text[], $2::text[]) AS q(word, prefix)
+            JOIN search_vocabulary v ON v.word % q.word OR v.word LIKE q.prefix
+            ORDER BY q.word, starts_with(v.word, q.word) DESC, similarity(v.word, q.word) DESC, v.popularity DESC
+            "#
+        )
+        .bind(&words)
+        .bind(&prefixes)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let corrections: HashMap<String, String> = corrections
+            .into_iter()
+        // Words that start a known word are being typed, not misspelled
+        .filter(|(word, known)| !known.starts_with(word.as_str()))
+        .collect();
+
+        if corrections.is_empty() {
+            return Ok(None);
+        }
+        let corrected: Vec<&str> = terms
+            .iter()
+            .map(|term| corrections.get(term).unwrap_or(term).as_str())
+            .collect();
+        Ok(Some(corrected.join(" ")))
+    }
+
+    /// Plugins whose name or ID starts with `query`, or whose pinyin or pinyin initials do,
+    /// then those spelled similarly. Localized names match too; each plugin is suggested
+    /// under its matching name in the best locale for `languages`.
+    pub async fn get_search_suggestions(&self, query: &str, languages: &[String]) -> sqlx::Result<Vec<String>> {
+        // `xtjk` or `xitong` find 系统监控
+        let pinyin = search::pinyin_query(query);
+        // Prefix matches score 1, misspelled names their similarity; popularity breaks ties
+        // and lifts well-known plugins over slightly closer obscure ones.
+        // Candidates come from the trigram and pinyin indexes, only those are scored
+        let mut tx = self.begin_search(true).await?;
+        let candidates = sqlx::query_as::<_, (String, String)>(
             r#"
-            SELECT DISTINCT name FROM plugins 
-            WHERE name ILIKE $1 AND status = 'active' 
-            ORDER BY downloads DESC 
+            SELECT id, name FROM (
+                SELECT p.id, p.name, p.downloads,
+                       CASE WHEN lower(p.name) LIKE $1 OR p.id ILIKE $1
+                              OR EXISTS (SELECT 1 FROM plugin_localizations pl
+                                         WHERE pl.plugin_id = p.id AND pl.version = p.current_version AND lower(pl.name) LIKE $1)
+                              OR EXISTS (SELECT 1 FROM plugin_name_pinyin py
+                                         WHERE py.plugin_id = p.id AND (py.pinyin LIKE $2 OR py.initials LIKE $2))
+                            THEN 1
+                            ELSE greatest(
+                                word_similarity($3, lower(p.name)),
+                                word_similarity($3, p.id),
+                                (SELECT max(word_similarity($3, lower(pl.name))) FROM plugin_localizations pl
+                                 WHERE pl.plugin_id = p.id AND pl.version = p.current_version))
+                       END AS score
+                FROM plugins p
+                WHERE p.status = 'active' AND p.id IN (
+                    SELECT x.id FROM plugins x
+                    WHERE lower(x.name) LIKE $1 OR x.id ILIKE $1 OR $3 <% lower(x.name) OR $3 <% x.id
+                    UNION
+                    SELECT pl.plugin_id FROM plugin_localizations pl WHERE lower(pl.name) LIKE $1 OR $3 <% lower(pl.name)
+                    UNION
+                    SELECT py.plugin_id FROM plugin_name_pinyin py WHERE py.pinyin LIKE $2 OR py.initials LIKE $2
+                )
+            ) candidates
+            WHERE score >= $4
+            ORDER BY score + 0.1 * log(downloads + 1) DESC, id
             LIMIT 10
             "#
         )
-        .bind(format!("{}%", query))
-        .fetch_all(&self.db_pool)
+        .bind(format!("{}%", search::escape_like(&query.to_lowercase())))
+        .bind(pinyin.as_ref().map(|key| format!("{}%", key)))
+        .bind(query.to_lowercase())
+        .bind(FUZZY_SIMILARITY)
+        .fetch_all(&mut *tx)
         .await?;
+        tx.commit().await?;
+
+        let ids: Vec<String> = candidates.iter().map(|(id, _)| id.clone()).collect();
+        let mut localized = self.get_current_localizations(&ids).await?;
+        let prefix = query.to_lowercase();
+        let matches = |name: &str| {
+            name.to_lowercase().starts_with(&prefix)
+                || pinyin.as_deref().is_some_and(|key| search::pinyin_matches(name, key))
+        };
+
+        let mut suggestions: Vec<String> = Vec::new();
+        for (id, name) in candidates {
+            let (default_locale, mut localizations) = localized.remove(&id).unwrap_or_default();
+            // Plugins found by similarity are suggested under the name the user would see
+            let fuzzy = !matches(&name) && !localizations.iter().any(|l| l.name.as_deref().is_some_and(matches));
+            localizations.retain(|l| l.name.as_deref().is_some_and(|n| fuzzy || matches(n)));
+            let default_matches = fuzzy || matches(&name);
+
+            let suggestion = match pick_localization(languages, default_locale.as_deref().filter(|_| default_matches), &localizations) {
+                Some(localization) => localization.name.clone(),
+                None if default_matches => Some(name),
+                None => localizations.into_iter().next().and_then(|l| l.name),
+            };
+            if let Some(suggestion) = suggestion.filter(|s| !suggestions.contains(s)) {
+                suggestions.push(suggestion);
+            }
+        }
 
         Ok(suggestions)
     }
-}
\ No newline at end of file
+}
+
+/// An image written to storage, ready to be recorded in plugin_media.
+struct StoredMedia {
+    kind: MediaKind,
+    format: &'static str,
+    width: u32,
+    height: u32,
+    file_path: String,
+    webp_path: String,
+    thumbnail_path: String,
+    thumbnail_webp_path: String,
+}
+
+/// Unpack a package and check its manifest off the async runtime, since decompressing a large
+/// archive can take seconds. Returns the upload back along with the package when its manifest
+/// could be parsed, and every problem found.
+async fn inspect_package(
+    data: Vec<u8>,
+) -> Result<(Vec<u8>, Option<InspectedPackage>, Vec<ManifestIssue>), UploadError> {
+    tokio::task::spawn_blocking(move || {
+        let (format, package) = match PluginPackage::from_bytes(&data) {
+            Ok(unpacked) => unpacked,
+            Err(e) => {
+                let issue = ManifestIssue::new("", format!("Invalid plugin package: {}", e));
+                return (data, None, vec![issue]);
+            }
+        };
+
+        let Some(info_json) = package.info_json() else {
+            return (data, None, vec![ManifestIssue::new("", "Plugin package missing info.json file")]);
+        };
+
+        match manifest::parse_manifest(&info_json.data) {
+            Ok(manifest) => {
+                let issues = manifest::validate_manifest(&manifest, &package);
+                let package = Arc::new(package);
+                (data, Some(InspectedPackage { format, package, manifest }), issues)
+            }
+            Err(issue) => (data, None, vec![issue]),
+        }
+    })
+    .await
+    .map_err(|e| UploadError::Storage(e.to_string()))
+}
+
+/// Validate and re-encode images off the async runtime. Every failure is reported,
+/// labelled with the image's path in the package.
+async fn process_images(
+    images: Vec<(MediaKind, String, Vec<u8>)>,
+) -> Result<Vec<(MediaKind, ProcessedImage)>, UploadError> {
+    let results = tokio::task::spawn_blocking(move || {
+        images
+            .into_iter()
+            .map(|(kind, path, data)| {
+                media::process_image(&data, kind)
+                    .map(|image| (kind, image))
+                    .map_err(|message| ManifestIssue::new("", format!("{}: {}", path, message)))
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| UploadError::Storage(e.to_string()))?;
+
+    let (processed, issues): (Vec<_>, Vec<_>) = results.into_iter().partition(|r| r.is_ok());
+    if !issues.is_empty() {
+        return Err(UploadError::Invalid(issues.into_iter().filter_map(Result::err).collect()));
+    }
+    Ok(processed.into_iter().filter_map(Result::ok).collect())
+}
+
+fn media_image(plugin_id: &str, id: i32, format: &str, width: i32, height: i32) -> PluginImage {
+    let base = format!("/api/v1/plugins/{}/media/{}", plugin_id, id);
+    PluginImage {
+        url: format!("{}/full.{}", base, format),
+        webp_url: format!("{}/full.webp", base),
+        thumbnail_url: format!("{}/thumb.{}", base, format),
+        thumbnail_webp_url: format!("{}/thumb.webp", base),
+        width,
+        height,
+    }
+}
+
+/// A version's name, description and README in one extra locale. Missing fields fall back
+/// to the default locale.
+struct Localization {
+    locale: String,
+    name: Option<String>,
+    description: Option<String>,
+    readme: Option<String>,
+}
+
+/// The text of a script for the search index, unless it is binary or too large.
+fn script_source(package: &PluginPackage, file: &str) -> Option<String> {
+    let entry = package.script(file)?;
+    if entry.data.len() > MAX_INDEXED_SCRIPT_SIZE || entry.data.contains(&0) {
+        return None;
+    }
+    String::from_utf8(entry.data.clone()).ok()
+}
+
+fn script_ngrams(name: &str, description: Option<&str>) -> Vec<String> {
+    search::cjk_ngrams(&format!("{} {}", name, description.unwrap_or_default()))
+}
+
+/// The README for a locale, or the default README.md for `None`.
+fn readme(package: &PluginPackage, locale: Option<&str>) -> Option<String> {
+    let entry = package.entries.iter().find(|entry| {
+        i18n::readme_locale(&entry.path)
+            .is_some_and(|found| found.map(i18n::canonical_locale).as_deref() == locale)
+    })?;
+    String::from_utf8(entry.data.clone()).ok()
+}
+
+/// Every localized variant declared in info.json or shipped as README.<locale>.md.
+fn localizations(inspected: &InspectedPackage) -> Vec<Localization> {
+    let (manifest, package) = (&inspected.manifest, &inspected.package);
+    let mut by_locale: BTreeMap<String, Localization> = BTreeMap::new();
+
+    for (locale, name) in &manifest.name_i18n {
+        localization_entry(&mut by_locale, package, locale).name = Some(name.trim().to_string());
+    }
+    for (locale, description) in &manifest.description_i18n {
+        localization_entry(&mut by_locale, package, locale).description = Some(description.clone());
+    }
+    for path in package.entries.iter().map(|e| e.path.as_str()) {
+        if let Some(Some(locale)) = i18n::readme_locale(path) {
+            localization_entry(&mut by_locale, package, locale);
+        }
+    }
+
+    by_locale.into_values().collect()
+}
+
+fn localization_entry<'a>(
+    by_locale: &'a mut BTreeMap<String, Localization>,
+    package: &PluginPackage,
+    locale: &str,
+) -> &'a mut Localization {
+    let locale = i18n::canonical_locale(locale);
+    by_locale.entry(locale.clone()).or_insert_with(|| Localization {
+        readme: readme(package, Some(&locale)),
+        locale,
+        name: None,
+        description: None,
+    })
+}
+
+/// The localization to show for the preferred languages, or `None` when the default
+/// locale is the better (or only) match.
+fn pick_localization<'a>(
+    languages: &[String],
+    default_locale: Option<&str>,
+    localizations: &'a [Localization],
+) -> Option<&'a Localization> {
+    let available: Vec<&str> = default_locale
+        .into_iter()
+        .chain(localizations.iter().map(|l| l.locale.as_str()))
+        .collect();
+    let chosen = i18n::negotiate(languages, &available)?;
+    localizations.iter().find(|l| l.locale == chosen)
+}
+
+/// Words in the description fragment of a search result.
+const HIGHLIGHT_WORDS: usize = 30;
+
+/// Lines either side of the first match in a script snippet.
+const SNIPPET_CONTEXT_LINES: usize = 2;
+/// Larger scripts are searchable by name and description only.
+const MAX_INDEXED_SCRIPT_SIZE: usize = 64 * 1024;
+
+/// Searches with fewer full-text matches than this also match names, IDs and tags by
+/// trigram similarity, and suggest a corrected query.
+const FUZZY_FALLBACK_BELOW: i64 = 5;
+/// Minimum `word_similarity` for a fuzzy match. `moniter` scores 0.625 against `monitor`.
+const FUZZY_SIMILARITY: f32 = 0.4;
+/// Minimum trigram `similarity` between a misspelled word and its correction.
+const DID_YOU_MEAN_SIMILARITY: f32 = 0.3;
+
+/// How often popularity scores and related plugins are recomputed.
+const SCORE_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+/// Age at which a day's downloads count half as much towards `trending_score`.
+const TRENDING_HALF_LIFE_DAYS: i32 = 3;
+/// Days of downloads that count towards `trending_score`. Older ones would weigh under 1%.
+const TRENDING_WINDOW_DAYS: i32 = 21;
+/// Days of downloads that make up `hot_score`, "hot this week".
+const HOT_WINDOW_DAYS: i32 = 7;
+
+/// Related plugins stored per plugin.
+pub const RELATED_PLUGINS: i64 = 10;
+/// Weights of tag overlap, a shared author and dependency overlap in related plugin scores.
+/// Overlaps are Jaccard indexes between 0 and 1.
+const RELATED_TAG_WEIGHT: f64 = 1.0;
+const RELATED_AUTHOR_WEIGHT: f64 = 0.3;
+const RELATED_DEPENDENCY_WEIGHT: f64 = 0.5;
+
+/// Most common values returned per search facet.
+const FACET_VALUES: i64 = 20;
+
+/// The WHERE conditions for a set of plugin filters, against `plugins p`, with every value
+/// passed as a bind parameter. Shared by searching and counting so that totals always match.
+pub struct FilterSql {
+    /// The whole WHERE clause.
+    conditions: String,
+    binds: Vec<String>,
+    terms: Vec<String>,
+    /// How well a plugin matches the search, higher is better.
+    rank: Option<String>,
+    /// Matching plugins, when already counted while resolving the filters.
+    count: Option<i64>,
+    /// Full-text search found too little and similar spellings match as well.
+    fuzzy: bool,
+}
+
+impl FilterSql {
+    /// With `fuzzy`, the search also matches names, IDs and tags that are spelled similarly.
+    fn new(filters: &PluginFilters, fuzzy: bool) -> Self {
+        let mut sql = Self {
+            conditions: match filters.include_deprecated {
+                true => "p.status IN ('active', 'deprecated')".to_string(),
+                false => "p.status = 'active'".to_string(),
+            },
+            binds: Vec::new(),
+            terms: Vec::new(),
+            rank: None,
+            count: None,
+            fuzzy,
+        };
+        let value = |v: &Option<String>| v.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+
+        if let Some(q) = value(&filters.search) {
+            sql.terms = search::search_terms(&q);
+            let mut matches = Vec::new();
+            let mut ranks = Vec::new();
+            let tsquery = search::tsquery(&sql.terms).map(|tsquery| sql.bind(tsquery));
+            if let Some(index) = tsquery {
+                ranks.push(format!("ts_rank(p.search_vector, ${}::tsquery)", index));
+            }
+            if fuzzy {
+                // Every branch is answered by an index; `<%` takes FUZZY_SIMILARITY from the
+                // transaction, see `begin_search`
+                let index = sql.bind(q.to_lowercase());
+                let mut sources: Vec<String> = tsquery
+                    .map(|tsquery| format!("SELECT x.id FROM plugins x WHERE x.search_vector @@ ${}::tsquery", tsquery))
+                    .into_iter()
+                    .collect();
+                sources.push(format!("SELECT x.id FROM plugins x WHERE ${0} <% lower(x.name) OR ${0} <% x.id", index));
+                sources.push(format!("SELECT pt.plugin_id FROM plugin_tags pt WHERE ${} <% lower(pt.tag)", index));
+                sources.push(format!(
+                    "SELECT pl.plugin_id FROM plugin_localizations pl \
+                     JOIN plugins x ON x.id = pl.plugin_id AND x.current_version = pl.version \
+                     WHERE ${} <% lower(pl.name)",
+                    index
+                ));
+                matches.push(format!("p.id IN ({})", sources.join(" UNION ")));
+                ranks.push(format!("greatest(word_similarity(${0}, lower(p.name)), word_similarity(${0}, p.id))", index));
+            } else if let Some(index) = tsquery {
+                matches.push(format!("p.search_vector @@ ${}::tsquery", index));
+            }
+
+            if matches.is_empty() {
+                // Only punctuation, nothing can match
+                sql.push("FALSE".to_string());
+            } else {
+                sql.push(format!("({})", matches.join(" OR ")));
+                sql.rank = Some(ranks.join(" + "));
+            }
+        }
+
+        let mut tags: Vec<String> = filters.tags.iter().map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()).collect();
+        tags.sort();
+        tags.dedup();
+        if !tags.is_empty() {
+            let placeholders: Vec<String> = tags.iter().map(|tag| format!("${}", sql.bind(tag.clone()))).collect();
+            let matching = format!(
+                "FROM plugin_tags pt WHERE pt.plugin_id = p.id AND lower(pt.tag) IN ({})",
+                placeholders.join(", ")
+            );
+            sql.push(match filters.all_tags {
+                true => format!("(SELECT COUNT(DISTINCT lower(pt.tag)) {}) = {}", matching, tags.len()),
+                false => format!("EXISTS (SELECT 1 {})", matching),
+            });
+        }
+
+        if let Some(author) = value(&filters.author) {
+            let index = sql.bind(author);
+            sql.push(format!("lower(p.author) = lower(${})", index));
+        }
+
+        let os = value(&filters.os);
+        let arch = value(&filters.arch);
+        if os.is_some() || arch.is_some() {
+            let mut platform_match = String::new();
+            if let Some(os) = os {
+                platform_match.push_str(&format!(" AND pp.os = ${}", sql.bind(os)));
+            }
+            if let Some(arch) = arch {
+                platform_match.push_str(&format!(" AND (pp.arch IS NULL OR pp.arch = ${})", sql.bind(arch)));
+            }
+            // Plugins that declare no platforms run everywhere
+            sql.push(format!(
+                "(NOT EXISTS (SELECT 1 FROM plugin_platforms pp WHERE pp.plugin_id = p.id AND pp.version = p.current_version) \
+                 OR EXISTS (SELECT 1 FROM plugin_platforms pp WHERE pp.plugin_id = p.id AND pp.version = p.current_version{}))",
+                platform_match
+            ));
+        }
+
+        if let Some(command) = value(&filters.requires) {
+            let index = sql.bind(command);
+            sql.push(format!(
+                "EXISTS (SELECT 1 FROM plugin_requirements pr WHERE pr.plugin_id = p.id AND pr.version = p.current_version AND pr.command = ${})",
+                index
+            ));
+        }
+
+        if let Some(capability) = value(&filters.provides) {
+            let index = sql.bind(capability);
+            sql.push(format!(
+                "EXISTS (SELECT 1 FROM plugin_provides pv WHERE pv.plugin_id = p.id AND pv.version = p.current_version AND pv.capability = ${})",
+                index
+            ));
+        }
+
+        if let Some(license) = value(&filters.license) {
+            let index = sql.bind(license);
+            sql.push(format!(
+                "EXISTS (SELECT 1 FROM unnest(p.license_ids) lid WHERE lower(lid) = lower(${0}) OR starts_with(lower(lid), lower(${0}) || '-'))",
+                index
+            ));
+        }
+
+        if let Some(osi_approved) = filters.osi_approved {
+            let index = sql.bind(osi_approved.to_string());
+            sql.push(format!("p.license_osi_approved = ${}::boolean", index));
+        }
+
+        if let Some(rating) = filters.min_rating {
+            let index = sql.bind(rating.to_string());
+            sql.push(format!("p.rating >= ${}::numeric", index));
+        }
+        if let Some(downloads) = filters.min_downloads {
+            let index = sql.bind(downloads.to_string());
+            sql.push(format!("p.downloads >= ${}::bigint", index));
+        }
+        if let Some(downloads) = filters.max_downloads {
+            let index = sql.bind(downloads.to_string());
+            sql.push(format!("p.downloads <= ${}::bigint", index));
+        }
+
+        let date_ranges = [
+            ("p.created_at >=", filters.created_after),
+            ("p.created_at <=", filters.created_before),
+            ("p.updated_at >=", filters.updated_after),
+            ("p.updated_at <=", filters.updated_before),
+        ];
+        for (comparison, date) in date_ranges {
+            if let Some(date) = date {
+                let index = sql.bind(date.to_rfc3339());
+                sql.push(format!("{} ${}::timestamptz", comparison, index));
+            }
+        }
+
+        if let Some(version) = value(&filters.geektools_version) {
+            let index = sql.bind(version);
+            sql.push(format!(
+                "(p.min_geektools_version IS NULL OR version_at_least(${}, p.min_geektools_version))",
+                index
+            ));
+        }
+
+        if let Some(executable) = filters.has_executable_scripts {
+            sql.push(format!(
+                "{}EXISTS (SELECT 1 FROM plugin_scripts ps WHERE ps.plugin_id = p.id AND ps.version = p.current_version AND ps.is_executable)",
+                if executable { "" } else { "NOT " }
+            ));
+        }
+
+        sql
+    }
+
+    /// `exact` with its full-text `count`, or the fuzzy form of `filters` when that found
+    /// fewer than `FUZZY_FALLBACK_BELOW` plugins.
+    fn with_fallback(filters: &PluginFilters, mut exact: Self, count: i64) -> Self {
+        if count >= FUZZY_FALLBACK_BELOW {
+            exact.count = Some(count);
+            return exact;
+        }
+        Self::new(filters, true)
+    }
+
+    /// Whether the search fell back to fuzzy matching. A correction is worth offering then,
+    /// however many plugins the similar spellings add.
+    pub fn is_fuzzy(&self) -> bool {
+        self.fuzzy
+    }
+
+    /// Add a bind parameter, returning its placeholder number.
+    fn bind(&mut self, value: String) -> usize {
+        self.binds.push(value);
+        self.binds.len()
+    }
+
+    fn push(&mut self, condition: String) {
+        self.conditions.push_str(" AND ");
+        self.conditions.push_str(&condition);
+    }
+}
+
+/// Columns of `plugins p` that `summary_from_row` reads.
+const SUMMARY_COLUMNS: &str = "p.id, p.name, p.description, p.author, p.current_version, p.downloads, p.rating, p.created_at, p.updated_at,
+    p.status = 'deprecated' AS deprecated,
+    ARRAY(SELECT pt.tag FROM plugin_tags pt WHERE pt.plugin_id = p.id ORDER BY pt.tag) AS tags,
+    ARRAY(SELECT pp.os FROM plugin_platforms pp WHERE pp.plugin_id = p.id AND pp.version = p.current_version ORDER BY pp.id) AS platform_os,
+    ARRAY(SELECT pp.arch FROM plugin_platforms pp WHERE pp.plugin_id = p.id AND pp.version = p.current_version ORDER BY pp.id) AS platform_arch,
+    ARRAY(SELECT pr.command FROM plugin_requirements pr WHERE pr.plugin_id = p.id AND pr.version = p.current_version ORDER BY pr.command) AS requires";
+
+/// A listed plugin, untranslated and without its icon.
+fn summary_from_row(row: &sqlx::postgres::PgRow) -> PluginSummary {
+    // Convert NUMERIC to f64
+    let rating: Option<sqlx::types::Decimal> = row.try_get("rating").ok();
+    let rating_decimal = rating.map(|d| BigDecimal::from_str(&d.to_string()).unwrap_or_else(|_| BigDecimal::from_str("0.00").unwrap()))
+        .unwrap_or_else(|| BigDecimal::from_str("0.00").unwrap());
+
+    let platform_os: Vec<String> = row.get("platform_os");
+    let platform_arch: Vec<Option<String>> = row.get("platform_arch");
+    let platforms = platform_os
+        .into_iter()
+        .zip(platform_arch)
+        .map(|(os, arch)| PluginPlatform { os, arch })
+        .collect();
+
+    PluginSummary {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        author: row.get("author"),
+        current_version: row.get("current_version"),
+        downloads: row.get("downloads"),
+        rating: rating_decimal,
+        tags: row.get("tags"),
+        platforms,
+        requires: row.get("requires"),
+        icon: None,
+        deprecated: row.get("deprecated"),
+        highlights: None,
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// File name friendly label for a platform, e.g. `linux-x86_64` or `macos`.
+fn platform_label(platform: &PluginPlatform) -> String {
+    match &platform.arch {
+        Some(arch) => format!("{}-{}", platform.os, arch),
+        None => platform.os.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_fallback_still_offers_correction() {
+        let filters = PluginFilters { search: Some("moniter".to_string()), ..Default::default() };
+
+        // Full-text search found nothing, so the typo falls back to similar spellings
+        let filter = FilterSql::with_fallback(&filters, FilterSql::new(&filters, false), 0);
+        assert!(filter.is_fuzzy());
+        assert!(filter.conditions.contains("<% lower(x.name)"));
+        // The total includes fuzzy matches, which must not hide the did-you-mean
+        assert_eq!(filter.count, None);
+
+        let filter = FilterSql::with_fallback(&filters, FilterSql::new(&filters, false), FUZZY_FALLBACK_BELOW);
+        assert!(!filter.is_fuzzy());
+        assert_eq!(filter.count, Some(FUZZY_FALLBACK_BELOW));
+    }
+}
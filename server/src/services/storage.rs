@@ -1,6 +1,5 @@
 use std::{path::PathBuf, sync::Arc};
 use tokio::fs;
-use uuid::Uuid;
 
 use crate::utils::config::Config;
 
@@ -27,11 +26,12 @@ impl StorageService {
         data: Vec<u8>,
         plugin_id: &str,
         version: &str,
+        extension: &str,
     ) -> anyhow::Result<String> {
         let plugin_dir = self.upload_dir.join("plugins").join(plugin_id).join(version);
         fs::create_dir_all(&plugin_dir).await?;
 
-        let filename = format!("{}-{}.tar.gz", plugin_id, version);
+        let filename = format!("{}-{}.{}", plugin_id, version, extension);
         let file_path = plugin_dir.join(&filename);
 
         fs::write(&file_path, data).await?;
@@ -39,23 +39,58 @@ impl StorageService {
         Ok(file_path.to_string_lossy().to_string())
     }
 
-    pub async fn store_temporary_file(&self, data: Vec<u8>) -> anyhow::Result<PathBuf> {
-        let temp_dir = self.upload_dir.join("temp");
-        fs::create_dir_all(&temp_dir).await?;
+    pub async fn store_platform_file(
+        &self,
+        data: Vec<u8>,
+        plugin_id: &str,
+        version: &str,
+        platform: &str,
+        extension: &str,
+    ) -> anyhow::Result<String> {
+        let plugin_dir = self.upload_dir.join("plugins").join(plugin_id).join(version);
+        fs::create_dir_all(&plugin_dir).await?;
 
-        let filename = format!("{}.tmp", Uuid::new_v4());
-        let file_path = temp_dir.join(filename);
+        let filename = format!("{}-{}-{}.{}", plugin_id, version, platform, extension);
+        let file_path = plugin_dir.join(&filename);
 
         fs::write(&file_path, data).await?;
 
-        Ok(file_path)
+        Ok(file_path.to_string_lossy().to_string())
     }
 
-    pub async fn cleanup_temporary_file(&self, file_path: &PathBuf) -> anyhow::Result<()> {
-        if file_path.exists() {
-            fs::remove_file(file_path).await?;
-        }
-        Ok(())
+    pub async fn store_delta_file(
+        &self,
+        data: Vec<u8>,
+        plugin_id: &str,
+        from_version: &str,
+        to_version: &str,
+    ) -> anyhow::Result<String> {
+        let plugin_dir = self.upload_dir.join("plugins").join(plugin_id).join(to_version);
+        fs::create_dir_all(&plugin_dir).await?;
+
+        let filename = format!("{}-{}-to-{}.delta.zst", plugin_id, from_version, to_version);
+        let file_path = plugin_dir.join(&filename);
+
+        fs::write(&file_path, data).await?;
+
+        Ok(file_path.to_string_lossy().to_string())
+    }
+
+    /// Images are named by the caller, typically after a hash of their content.
+    pub async fn store_media_file(
+        &self,
+        data: Vec<u8>,
+        plugin_id: &str,
+        version: &str,
+        filename: &str,
+    ) -> anyhow::Result<String> {
+        let media_dir = self.upload_dir.join("plugins").join(plugin_id).join(version).join("media");
+        fs::create_dir_all(&media_dir).await?;
+
+        let file_path = media_dir.join(filename);
+        fs::write(&file_path, data).await?;
+
+        Ok(file_path.to_string_lossy().to_string())
     }
 
     pub fn get_file_url(&self, file_path: &str) -> String {
@@ -0,0 +1,95 @@
+use std::io;
+use zstd::zstd_safe::{CParameter, DParameter};
+
+/// Deltas are plain zstd frames compressed against the previous version's archive, exactly as
+/// a full download serves it, which is loaded as a raw content dictionary (the same idea as
+/// `zstd --patch-from`).
+const DELTA_LEVEL: i32 = 19;
+
+/// Largest window a default zstd decoder accepts without extra memory flags.
+const MAX_WINDOW_LOG: u32 = 27;
+
+/// A delta from `base` to `target`, checked to reproduce the target exactly.
+pub fn build_delta(base: &[u8], target: &[u8]) -> io::Result<Vec<u8>> {
+    let delta = create_delta(base, target)?;
+    if apply_delta(base, &delta, target.len())? != target {
+        return Err(io::Error::other("delta does not reproduce the target archive"));
+    }
+    Ok(delta)
+}
+
+pub fn create_delta(base: &[u8], target: &[u8]) -> io::Result<Vec<u8>> {
+    let mut compressor = zstd::bulk::Compressor::with_dictionary(DELTA_LEVEL, base)?;
+    // The window has to reach back over the whole base, or matches against it are lost
+    compressor.set_parameter(CParameter::WindowLog(window_log(base.len() + target.len())))?;
+    compressor.compress(target)
+}
+
+pub fn apply_delta(base: &[u8], delta: &[u8], target_size: usize) -> io::Result<Vec<u8>> {
+    let mut decompressor = zstd::bulk::Decompressor::with_dictionary(base)?;
+    decompressor.set_parameter(DParameter::WindowLogMax(MAX_WINDOW_LOG))?;
+    decompressor.decompress(delta, target_size)
+}
+
+fn window_log(size: usize) -> u32 {
+    let bits = usize::BITS - size.max(1).leading_zeros();
+    bits.clamp(10, MAX_WINDOW_LOG)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Incompressible on its own, so a delta can only be small by matching the base.
+    fn sample(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_delta_round_trip() {
+        let base = sample(1, 200_000);
+        let mut target = base.clone();
+        target[1000..1100].copy_from_slice(&sample(9, 100));
+        target.extend_from_slice(b"appended script");
+
+        let delta = create_delta(&base, &target).unwrap();
+        assert!(delta.len() < 2_000);
+        assert_eq!(apply_delta(&base, &delta, target.len()).unwrap(), target);
+    }
+
+    #[test]
+    fn test_delta_needs_the_right_base() {
+        let base = sample(1, 50_000);
+        let mut target = base.clone();
+        target[..100].copy_from_slice(&sample(2, 100));
+        let delta = create_delta(&base, &target).unwrap();
+
+        let wrong_base = sample(3, 50_000);
+        assert!(apply_delta(&wrong_base, &delta, target.len()).map_or(true, |out| out != target));
+    }
+
+    #[test]
+    fn test_delta_patches_downloaded_archive() {
+        use crate::utils::package::tests::build_tar_gz;
+        use sha2::{Digest, Sha256};
+
+        let library = sample(4, 300_000);
+        let base = build_tar_gz(&[("info.json", b"{\"version\":\"1.0.0\"}", 0o644), ("lib.bin", &library, 0o644)]);
+        let target = build_tar_gz(&[
+            ("info.json", b"{\"version\":\"1.1.0\"}", 0o644),
+            ("lib.bin", &library, 0o644),
+            ("run.sh", b"echo new", 0o755),
+        ]);
+
+        let delta = build_delta(&base, &target).unwrap();
+        assert!(delta.len() < target.len() / 2);
+        let patched = apply_delta(&base, &delta, target.len()).unwrap();
+        assert_eq!(Sha256::digest(&patched), Sha256::digest(&target));
+    }
+}
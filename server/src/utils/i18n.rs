@@ -0,0 +1,125 @@
+/// Localized READMEs larger than this are rejected at upload.
+pub const MAX_README_SIZE: usize = 256 * 1024;
+
+/// At most this many languages are taken from an Accept-Language header.
+const MAX_ACCEPTED_LANGUAGES: usize = 10;
+
+/// Normalize the case of a language tag: `zh-hant-tw` becomes `zh-Hant-TW`.
+pub fn canonical_locale(tag: &str) -> String {
+    tag.split('-')
+        .enumerate()
+        .map(|(i, part)| match (i, part.len()) {
+            (0, _) => part.to_ascii_lowercase(),
+            (_, 4) if part.chars().all(|c| c.is_ascii_alphabetic()) => {
+                let (first, rest) = part.split_at(1);
+                format!("{}{}", first.to_ascii_uppercase(), rest.to_ascii_lowercase())
+            }
+            (_, 2) => part.to_ascii_uppercase(),
+            _ => part.to_ascii_lowercase(),
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Languages from an Accept-Language header, most preferred first. Wildcards and
+/// languages with `q=0` are dropped.
+pub fn parse_accept_language(header: &str) -> Vec<String> {
+    let mut languages: Vec<(String, f32)> = header
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            if tag.is_empty() || tag == "*" || quality <= 0.0 {
+                return None;
+            }
+            Some((canonical_locale(tag), quality))
+        })
+        .collect();
+
+    // Stable, so equally weighted languages keep their header order
+    languages.sort_by(|a, b| b.1.total_cmp(&a.1));
+    languages.dedup_by(|a, b| a.0 == b.0);
+    languages.truncate(MAX_ACCEPTED_LANGUAGES);
+    languages.into_iter().map(|(tag, _)| tag).collect()
+}
+
+/// The available locale that best serves the preferred languages, or `None` when none of
+/// them is available. Each language is tried exactly, then with subtags removed
+/// (`zh-Hant-TW`, `zh-Hant`, `zh`), then against any variant of the same language,
+/// before moving on to the next one.
+pub fn negotiate<'a>(preferred: &[String], available: &[&'a str]) -> Option<&'a str> {
+    let find = |tag: &str| available.iter().copied().find(|locale| locale.eq_ignore_ascii_case(tag));
+
+    preferred.iter().find_map(|language| {
+        let mut tag = language.as_str();
+        loop {
+            if let Some(locale) = find(tag) {
+                return Some(locale);
+            }
+            match tag.rfind('-') {
+                Some(i) => tag = &tag[..i],
+                None => break,
+            }
+        }
+        available
+            .iter()
+            .copied()
+            .find(|locale| primary_language(locale).eq_ignore_ascii_case(tag))
+    })
+}
+
+fn primary_language(tag: &str) -> &str {
+    tag.split('-').next().unwrap_or(tag)
+}
+
+/// For a README at the package root, the locale it is written in: `Some(None)` for the
+/// default README.md, `Some(Some("zh-CN"))` for README.zh-CN.md, `None` for anything else.
+pub fn readme_locale(path: &str) -> Option<Option<&str>> {
+    let stem = path.strip_suffix(".md").or_else(|| path.strip_suffix(".MD"))?;
+    if stem.contains('/') || !stem.get(..6)?.eq_ignore_ascii_case("readme") {
+        return None;
+    }
+    match &stem[6..] {
+        "" => Some(None),
+        rest => rest.strip_prefix('.').filter(|locale| !locale.is_empty()).map(Some),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_accept_language() {
+        assert_eq!(
+            parse_accept_language("en-US;q=0.8, zh-cn, *;q=0.1, fr;q=0, en;q=0.8"),
+            vec!["zh-CN", "en-US", "en"]
+        );
+        assert!(parse_accept_language("").is_empty());
+        assert_eq!(canonical_locale("ZH-hant-tw"), "zh-Hant-TW");
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let available = ["en", "zh-CN", "pt-BR"];
+        let prefs = |header: &str| parse_accept_language(header);
+
+        assert_eq!(negotiate(&prefs("zh-CN,en"), &available), Some("zh-CN"));
+        assert_eq!(negotiate(&prefs("en-GB,zh-CN"), &available), Some("en"));
+        assert_eq!(negotiate(&prefs("zh"), &available), Some("zh-CN"));
+        assert_eq!(negotiate(&prefs("pt-PT"), &available), Some("pt-BR"));
+        assert_eq!(negotiate(&prefs("de, ja"), &available), None);
+    }
+
+    #[test]
+    fn test_readme_locale() {
+        assert_eq!(readme_locale("README.md"), Some(None));
+        assert_eq!(readme_locale("readme.zh-CN.md"), Some(Some("zh-CN")));
+        assert_eq!(readme_locale("docs/README.md"), None);
+        assert_eq!(readme_locale("README.txt"), None);
+        assert_eq!(readme_locale("READMEFIRST.md"), None);
+    }
+}
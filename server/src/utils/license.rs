@@ -0,0 +1,128 @@
+use spdx::{
+    expression::{ExprNode, Operator},
+    Expression, LicenseItem, ParseMode,
+};
+
+/// A plugin's license as stored: an SPDX expression in canonical form, or free text that
+/// is explained by a LICENSE file in the package.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LicenseInfo {
+    pub expression: String,
+    pub spdx: bool,
+    /// Every license named in the expression, e.g. `GPL-3.0-or-later` and `MIT`.
+    pub ids: Vec<String>,
+    /// Whether the expression can be satisfied using OSI-approved licenses only.
+    pub osi_approved: bool,
+}
+
+/// Parse a license expression. Common spellings (`mit`, `Apache 2.0`, `MIT/Apache-2.0`) are
+/// accepted and normalized. Anything that is not a known SPDX license, including
+/// `LicenseRef-` licenses, is only accepted when the package bundles a LICENSE file.
+pub fn parse_license(text: &str, has_license_file: bool) -> Result<LicenseInfo, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("License cannot be empty".to_string());
+    }
+
+    let parsed = Expression::parse_mode(text, ParseMode::LAX)
+        .map_err(|e| format!("Invalid SPDX license expression: {}", e.reason));
+    let custom = match &parsed {
+        Ok(expression) => expression
+            .requirements()
+            .any(|r| !matches!(r.req.license, LicenseItem::Spdx { .. })),
+        Err(_) => true,
+    };
+
+    if custom && !has_license_file {
+        return Err(match parsed {
+            Err(message) => format!("{}; bundle a LICENSE file to use a custom license", message),
+            Ok(_) => "Custom licenses (LicenseRef-) need a LICENSE file in the package".to_string(),
+        });
+    }
+
+    let Ok(expression) = parsed else {
+        return Ok(LicenseInfo {
+            expression: text.to_string(),
+            spdx: false,
+            ids: Vec::new(),
+            osi_approved: false,
+        });
+    };
+
+    let mut ids: Vec<String> = expression.requirements().map(|r| r.req.license.to_string()).collect();
+    ids.sort();
+    ids.dedup();
+
+    Ok(LicenseInfo {
+        expression: normalize(&expression),
+        spdx: !custom,
+        ids,
+        osi_approved: expression.evaluate(|req| match req.license {
+            LicenseItem::Spdx { id, .. } => id.is_osi_approved(),
+            LicenseItem::Other { .. } => false,
+        }),
+    })
+}
+
+/// Whether a single license ID from `LicenseInfo::ids` is OSI-approved.
+pub fn is_osi_approved(id: &str) -> bool {
+    spdx::license_id(id.trim_end_matches('+')).is_some_and(|license| license.is_osi_approved())
+}
+
+/// Rebuild the expression from its parsed form with canonical identifiers and operators,
+/// adding parentheses only where `OR` sits inside `AND`.
+fn normalize(expression: &Expression) -> String {
+    let mut stack: Vec<(String, Option<Operator>)> = Vec::new();
+    for node in expression.iter() {
+        match node {
+            ExprNode::Req(req) => stack.push((req.req.to_string(), None)),
+            ExprNode::Op(op) => {
+                let (Some(right), Some(left)) = (stack.pop(), stack.pop()) else {
+                    continue;
+                };
+                let wrap = |(text, inner): (String, Option<Operator>)| match (op, inner) {
+                    (Operator::And, Some(Operator::Or)) => format!("({})", text),
+                    _ => text,
+                };
+                let joiner = if *op == Operator::And { "AND" } else { "OR" };
+                stack.push((format!("{} {} {}", wrap(left), joiner, wrap(right)), Some(*op)));
+            }
+        }
+    }
+    stack.pop().map(|(text, _)| text).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_license() {
+        let license = parse_license("mit", false).unwrap();
+        assert_eq!(license.expression, "MIT");
+        assert!(license.spdx && license.osi_approved);
+
+        let license = parse_license("MIT/Apache-2.0 AND (BSD-3-Clause or ISC)", false).unwrap();
+        assert_eq!(license.expression, "MIT OR Apache-2.0 AND (BSD-3-Clause OR ISC)");
+        assert_eq!(license.ids, vec!["Apache-2.0", "BSD-3-Clause", "ISC", "MIT"]);
+        assert!(is_osi_approved("Apache-2.0+") && !is_osi_approved("LicenseRef-Acme"));
+
+        let license = parse_license("GPL-2.0+", false).unwrap();
+        assert_eq!(license.expression, "GPL-2.0-or-later");
+        assert!(license.osi_approved);
+    }
+
+    #[test]
+    fn test_custom_license_needs_license_file() {
+        assert!(parse_license("Proprietary", false).is_err());
+        assert!(parse_license("LicenseRef-Acme", false).is_err());
+        assert!(parse_license("", true).is_err());
+
+        let license = parse_license("Proprietary", true).unwrap();
+        assert!(!license.spdx && !license.osi_approved && license.ids.is_empty());
+
+        let license = parse_license("MIT OR LicenseRef-Acme", true).unwrap();
+        assert!(!license.spdx && license.osi_approved);
+        assert_eq!(license.ids, vec!["LicenseRef-Acme", "MIT"]);
+    }
+}
@@ -0,0 +1,604 @@
+use serde_json::Value;
+use std::collections::HashSet;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+use crate::{
+    models::{CreatePluginRequest, FundingLink, FundingType, ManifestIssue, ParameterType, PluginScriptInfo},
+    utils::{
+        i18n::{canonical_locale, readme_locale, MAX_README_SIZE},
+        license::parse_license,
+        media::{self, MediaKind},
+        package::PluginPackage,
+        plugin_config::validate_config_schema,
+        version::VersionRange,
+        validation::{
+            validate_env_var, validate_https_url, validate_locale, validate_output_format, validate_parameter_name,
+            validate_permission_path, validate_platform, validate_plugin_id, validate_plugin_id_regex,
+            validate_required_command, validate_script_file, validate_service_name, validate_version,
+        },
+    },
+};
+
+pub const CURRENT_MANIFEST_VERSION: u32 = 1;
+
+const MAX_FUNDING_LINKS: usize = 10;
+
+const SCHEMA_V1: &str = include_str!("../../schemas/info.v1.json");
+
+/// The published JSON Schema for a manifest version.
+pub fn schema(version: u32) -> Option<&'static str> {
+    match version {
+        1 => Some(SCHEMA_V1),
+        _ => None,
+    }
+}
+
+/// Deserialize info.json, reporting where in the document parsing failed.
+pub fn parse_manifest(contents: &[u8]) -> Result<CreatePluginRequest, ManifestIssue> {
+    let deserializer = &mut serde_json::Deserializer::from_slice(contents);
+    serde_path_to_error::deserialize(deserializer).map_err(|err| {
+        let pointer = err
+            .path()
+            .iter()
+            .filter_map(|segment| match segment {
+                serde_path_to_error::Segment::Seq { index } => Some(index.to_string()),
+                serde_path_to_error::Segment::Map { key } => Some(escape_pointer(key)),
+                serde_path_to_error::Segment::Enum { variant } => Some(escape_pointer(variant)),
+                serde_path_to_error::Segment::Unknown => None,
+            })
+            .fold(String::new(), |pointer, segment| format!("{}/{}", pointer, segment));
+        ManifestIssue::new(pointer, err.inner().to_string())
+    })
+}
+
+/// Run every manifest check that does not need the database, sorted by pointer.
+pub fn validate_manifest(manifest: &CreatePluginRequest, package: &PluginPackage) -> Vec<ManifestIssue> {
+    let mut issues = Vec::new();
+
+    if manifest.manifest_version == 0 || manifest.manifest_version > CURRENT_MANIFEST_VERSION {
+        issues.push(ManifestIssue::new(
+            "/manifest_version",
+            format!(
+                "Unsupported manifest version {}, this server supports up to {}",
+                manifest.manifest_version, CURRENT_MANIFEST_VERSION
+            ),
+        ));
+    }
+
+    if let Err(errors) = manifest.validate() {
+        collect_validation_errors("", &errors, &mut issues);
+    }
+
+    // Length is already covered by the derived validation
+    if !validate_plugin_id_regex(&manifest.id) {
+        issues.push(ManifestIssue::new(
+            "/id",
+            "Plugin ID can only contain lowercase letters, numbers, underscores, and hyphens",
+        ));
+    }
+
+    if let Err(message) = validate_version(&manifest.version) {
+        issues.push(ManifestIssue::new("/version", message));
+    }
+
+    if let Some(min_version) = &manifest.min_geektools_version {
+        if let Err(message) = validate_version(min_version) {
+            issues.push(ManifestIssue::new("/min_geektools_version", message));
+        }
+    }
+
+    if let Some(license) = &manifest.license {
+        if let Err(message) = parse_license(license, package.license_file().is_some()) {
+            issues.push(ManifestIssue::new("/license", message));
+        }
+    }
+
+    if manifest.tags.len() > 10 {
+        issues.push(ManifestIssue::new("/tags", "Maximum 10 tags allowed"));
+    }
+    let mut unique_tags = HashSet::new();
+    for (i, tag) in manifest.tags.iter().enumerate() {
+        if tag.len() > 50 {
+            issues.push(ManifestIssue::new(format!("/tags/{}", i), "Tag length cannot exceed 50 characters"));
+        }
+        if !unique_tags.insert(tag.to_lowercase()) {
+            issues.push(ManifestIssue::new(format!("/tags/{}", i), format!("Duplicate tag: {}", tag)));
+        }
+    }
+
+    for (i, script) in manifest.scripts.iter().enumerate() {
+        let pointer = format!("/scripts/{}/file", i);
+        if let Err(message) = validate_script_file(&script.file) {
+            issues.push(ManifestIssue::new(pointer, message));
+        } else if package.script(&script.file).is_none() {
+            issues.push(ManifestIssue::new(
+                pointer,
+                format!("Script file not found in package: {}", script.file),
+            ));
+        }
+        validate_script_interface(&format!("/scripts/{}", i), script, &mut issues);
+    }
+
+    // Hooks run on the user's machine just like scripts, so they get the same file checks
+    for (hook, file) in manifest.hooks.declared() {
+        let pointer = format!("/hooks/{}", hook);
+        if let Err(message) = validate_script_file(file) {
+            issues.push(ManifestIssue::new(pointer, message));
+        } else if package.script(file).is_none() {
+            issues.push(ManifestIssue::new(pointer, format!("Hook script not found in package: {}", file)));
+        }
+    }
+
+    for (i, dep) in manifest.dependencies.iter().enumerate() {
+        if dep.id == manifest.id {
+            issues.push(ManifestIssue::new(format!("/dependencies/{}/id", i), "A plugin cannot depend on itself"));
+        } else if let Err(message) = validate_plugin_id(&dep.id) {
+            issues.push(ManifestIssue::new(format!("/dependencies/{}/id", i), message));
+        }
+        if let Some(min_version) = &dep.min_version {
+            if let Err(message) = validate_version(min_version) {
+                issues.push(ManifestIssue::new(format!("/dependencies/{}/min_version", i), message));
+            }
+        }
+        match dep.reason.as_deref().map(str::trim) {
+            Some(reason) if reason.len() > 255 => {
+                issues.push(ManifestIssue::new(
+                    format!("/dependencies/{}/reason", i),
+                    "Dependency reason cannot exceed 255 characters",
+                ));
+            }
+            None | Some("") if dep.optional => {
+                issues.push(ManifestIssue::new(
+                    format!("/dependencies/{}/reason", i),
+                    "Optional dependencies must give a reason",
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    for (i, conflict) in manifest.conflicts.iter().enumerate() {
+        if conflict.id == manifest.id {
+            issues.push(ManifestIssue::new(format!("/conflicts/{}/id", i), "A plugin cannot conflict with itself"));
+        } else if let Err(message) = validate_plugin_id(&conflict.id) {
+            issues.push(ManifestIssue::new(format!("/conflicts/{}/id", i), message));
+        } else if manifest.dependencies.iter().any(|dep| dep.id == conflict.id && !dep.optional) {
+            issues.push(ManifestIssue::new(
+                format!("/conflicts/{}/id", i),
+                format!("{} is both a dependency and a conflict", conflict.id),
+            ));
+        }
+        if let Some(range) = &conflict.versions {
+            if let Err(message) = VersionRange::parse(range) {
+                issues.push(ManifestIssue::new(format!("/conflicts/{}/versions", i), message));
+            }
+        }
+    }
+
+    let mut capabilities = HashSet::new();
+    for (i, capability) in manifest.provides.iter().enumerate() {
+        let pointer = format!("/provides/{}", i);
+        if capability == &manifest.id {
+            issues.push(ManifestIssue::new(pointer, "A plugin does not need to provide its own ID"));
+        } else if let Err(message) = validate_plugin_id(capability) {
+            issues.push(ManifestIssue::new(pointer, message));
+        } else if !capabilities.insert(capability.as_str()) {
+            issues.push(ManifestIssue::new(pointer, format!("Duplicate capability: {}", capability)));
+        }
+    }
+
+    for (i, platform) in manifest.platforms.iter().enumerate() {
+        if let Err(message) = validate_platform(&platform.os, platform.arch.as_deref()) {
+            issues.push(ManifestIssue::new(format!("/platforms/{}", i), message));
+        }
+    }
+
+    for (i, command) in manifest.requires.iter().enumerate() {
+        if let Err(message) = validate_required_command(command) {
+            issues.push(ManifestIssue::new(format!("/requires/{}", i), message));
+        }
+    }
+
+    let permissions = &manifest.permissions;
+    let mut unique_paths = HashSet::new();
+    for (i, fs) in permissions.filesystem.iter().enumerate() {
+        let pointer = format!("/permissions/filesystem/{}/path", i);
+        if let Err(message) = validate_permission_path(&fs.path) {
+            issues.push(ManifestIssue::new(pointer, message));
+        } else if !unique_paths.insert(fs.path.as_str()) {
+            issues.push(ManifestIssue::new(pointer, format!("Duplicate filesystem permission: {}", fs.path)));
+        }
+    }
+    for (i, service) in permissions.services.iter().enumerate() {
+        if let Err(message) = validate_service_name(service) {
+            issues.push(ManifestIssue::new(format!("/permissions/services/{}", i), message));
+        }
+    }
+    for (i, name) in permissions.env.iter().enumerate() {
+        if let Err(message) = validate_env_var(name) {
+            issues.push(ManifestIssue::new(format!("/permissions/env/{}", i), message));
+        }
+    }
+
+    if let Some(config) = &manifest.config {
+        issues.extend(validate_config_schema(config));
+    }
+
+    for (pointer, url) in [("/issues_url", &manifest.issues_url), ("/docs_url", &manifest.docs_url)] {
+        if let Some(Err(message)) = url.as_deref().map(validate_https_url) {
+            issues.push(ManifestIssue::new(pointer, message));
+        }
+    }
+    validate_funding(&manifest.funding, &mut issues);
+
+    validate_localizations(manifest, package, &mut issues);
+
+    let media = media::package_media(package);
+    if media.iter().filter(|(kind, _)| *kind == MediaKind::Screenshot).count() > media::MAX_SCREENSHOTS {
+        issues.push(ManifestIssue::new("", format!("Maximum {} screenshots allowed", media::MAX_SCREENSHOTS)));
+    }
+    for (kind, entry) in media {
+        if let Err(message) = media::check_image(&entry.data, kind) {
+            issues.push(ManifestIssue::new("", format!("{}: {}", entry.path, message)));
+        }
+    }
+
+    issues.sort_by(|a, b| a.pointer.cmp(&b.pointer));
+    issues
+}
+
+fn validate_funding(funding: &[FundingLink], issues: &mut Vec<ManifestIssue>) {
+    if funding.len() > MAX_FUNDING_LINKS {
+        issues.push(ManifestIssue::new("/funding", format!("Maximum {} funding links allowed", MAX_FUNDING_LINKS)));
+    }
+
+    let mut unique = HashSet::new();
+    for (i, link) in funding.iter().enumerate() {
+        let pointer = format!("/funding/{}/url", i);
+        let url = match validate_https_url(&link.url) {
+            Ok(url) => url,
+            Err(message) => {
+                issues.push(ManifestIssue::new(pointer, message));
+                continue;
+            }
+        };
+
+        let host = url.host_str().unwrap_or_default();
+        if let Some(expected) = link.funding_type.host() {
+            if host != expected && host.strip_prefix("www.") != Some(expected) {
+                issues.push(ManifestIssue::new(pointer, format!("Link must point at {}", expected)));
+                continue;
+            }
+        }
+        if link.funding_type == FundingType::GithubSponsors && !url.path().starts_with("/sponsors/") {
+            issues.push(ManifestIssue::new(pointer, "GitHub Sponsors links look like https://github.com/sponsors/<name>"));
+        } else if !unique.insert(url.as_str().to_string()) {
+            issues.push(ManifestIssue::new(pointer, format!("Duplicate funding link: {}", link.url)));
+        }
+    }
+}
+
+/// Localized names, descriptions and READMEs.
+fn validate_localizations(manifest: &CreatePluginRequest, package: &PluginPackage, issues: &mut Vec<ManifestIssue>) {
+    let default_locale = manifest.default_locale.as_deref().map(canonical_locale);
+    if let Some(locale) = &manifest.default_locale {
+        if let Err(message) = validate_locale(locale) {
+            issues.push(ManifestIssue::new("/default_locale", message));
+        }
+    }
+
+    for (field, texts) in [("name_i18n", &manifest.name_i18n), ("description_i18n", &manifest.description_i18n)] {
+        let mut seen = HashSet::new();
+        for (locale, text) in texts {
+            let pointer = format!("/{}/{}", field, escape_pointer(locale));
+            if let Err(message) = validate_locale(locale) {
+                issues.push(ManifestIssue::new(pointer, message));
+            } else if !seen.insert(canonical_locale(locale)) {
+                issues.push(ManifestIssue::new(pointer, format!("Duplicate locale: {}", locale)));
+            } else if default_locale.as_deref() == Some(canonical_locale(locale).as_str()) {
+                issues.push(ManifestIssue::new(pointer, "This is the default locale, set the unlocalized field instead"));
+            } else if field == "name_i18n" && (text.trim().is_empty() || text.chars().count() > 255) {
+                issues.push(ManifestIssue::new(pointer, "Localized name must be 1 to 255 characters"));
+            }
+        }
+    }
+
+    let mut readme_locales = HashSet::new();
+    for entry in &package.entries {
+        let Some(locale) = readme_locale(&entry.path) else {
+            continue;
+        };
+        if let Some(locale) = locale {
+            if let Err(message) = validate_locale(locale) {
+                issues.push(ManifestIssue::new("", format!("{}: {}", entry.path, message)));
+                continue;
+            }
+        }
+        if !readme_locales.insert(locale.map(canonical_locale)) {
+            issues.push(ManifestIssue::new("", format!("{}: duplicate README for this locale", entry.path)));
+        } else if entry.data.len() > MAX_README_SIZE {
+            issues.push(ManifestIssue::new(
+                "",
+                format!("{}: README cannot exceed {} KB", entry.path, MAX_README_SIZE / 1024),
+            ));
+        } else if std::str::from_utf8(&entry.data).is_err() {
+            issues.push(ManifestIssue::new("", format!("{}: README must be UTF-8 text", entry.path)));
+        }
+    }
+}
+
+/// Parameters, exit codes and output formats of one script.
+fn validate_script_interface(pointer: &str, script: &PluginScriptInfo, issues: &mut Vec<ManifestIssue>) {
+    let mut names = HashSet::new();
+    for (i, param) in script.parameters.iter().enumerate() {
+        let pointer = format!("{}/parameters/{}", pointer, i);
+        if let Err(message) = validate_parameter_name(&param.name) {
+            issues.push(ManifestIssue::new(format!("{}/name", pointer), message));
+        } else if !names.insert(param.name.as_str()) {
+            issues.push(ManifestIssue::new(
+                format!("{}/name", pointer),
+                format!("Duplicate parameter: {}", param.name),
+            ));
+        }
+
+        if !param.choices.is_empty() && param.param_type == ParameterType::Boolean {
+            issues.push(ManifestIssue::new(format!("{}/enum", pointer), "Boolean parameters cannot have choices"));
+        }
+        for (j, choice) in param.choices.iter().enumerate() {
+            if !value_matches(param.param_type, choice) {
+                issues.push(ManifestIssue::new(
+                    format!("{}/enum/{}", pointer, j),
+                    format!("Choice does not match parameter type {:?}", param.param_type).to_lowercase(),
+                ));
+            }
+        }
+
+        if let Some(default) = &param.default {
+            if param.required {
+                issues.push(ManifestIssue::new(
+                    format!("{}/default", pointer),
+                    "A required parameter cannot have a default",
+                ));
+            } else if !value_matches(param.param_type, default) {
+                issues.push(ManifestIssue::new(
+                    format!("{}/default", pointer),
+                    format!("Default does not match parameter type {:?}", param.param_type).to_lowercase(),
+                ));
+            } else if !param.choices.is_empty() && !param.choices.contains(default) {
+                issues.push(ManifestIssue::new(format!("{}/default", pointer), "Default must be one of the choices"));
+            }
+        }
+    }
+
+    let mut codes = HashSet::new();
+    for (i, exit_code) in script.exit_codes.iter().enumerate() {
+        let pointer = format!("{}/exit_codes/{}/code", pointer, i);
+        if !(0..=255).contains(&exit_code.code) {
+            issues.push(ManifestIssue::new(pointer, "Exit codes must be between 0 and 255"));
+        } else if !codes.insert(exit_code.code) {
+            issues.push(ManifestIssue::new(pointer, format!("Duplicate exit code: {}", exit_code.code)));
+        }
+    }
+
+    let mut formats = HashSet::new();
+    for (i, format) in script.output_formats.iter().enumerate() {
+        let pointer = format!("{}/output_formats/{}", pointer, i);
+        if let Err(message) = validate_output_format(format) {
+            issues.push(ManifestIssue::new(pointer, message));
+        } else if !formats.insert(format.as_str()) {
+            issues.push(ManifestIssue::new(pointer, format!("Duplicate output format: {}", format)));
+        }
+    }
+}
+
+fn value_matches(param_type: ParameterType, value: &Value) -> bool {
+    match param_type {
+        ParameterType::String | ParameterType::Path => value.is_string(),
+        ParameterType::Integer => value.is_i64() || value.is_u64(),
+        ParameterType::Number => value.is_number(),
+        ParameterType::Boolean => value.is_boolean(),
+    }
+}
+
+fn collect_validation_errors(prefix: &str, errors: &ValidationErrors, issues: &mut Vec<ManifestIssue>) {
+    for (field, kind) in errors.errors() {
+        let pointer = format!("{}/{}", prefix, field);
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                for error in field_errors {
+                    issues.push(ManifestIssue::new(&pointer, describe_validation_error(field, error)));
+                }
+            }
+            ValidationErrorsKind::Struct(inner) => collect_validation_errors(&pointer, inner, issues),
+            ValidationErrorsKind::List(items) => {
+                for (index, inner) in items {
+                    collect_validation_errors(&format!("{}/{}", pointer, index), inner, issues);
+                }
+            }
+        }
+    }
+}
+
+fn describe_validation_error(field: &str, error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+
+    match error.code.as_ref() {
+        "length" => match (error.params.get("min"), error.params.get("max")) {
+            (Some(min), Some(max)) => format!("{} must have a length between {} and {}", field, min, max),
+            (Some(min), None) => format!("{} must have a length of at least {}", field, min),
+            (None, Some(max)) => format!("{} must have a length of at most {}", field, max),
+            (None, None) => format!("Invalid {}", field),
+        },
+        _ => format!("Invalid {}", field),
+    }
+}
+
+fn escape_pointer(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::package::tests::build_tar_gz;
+
+    const INFO_JSON: &str = include_str!("../../info.json");
+
+    fn package_with(files: &[(&str, &[u8], u32)]) -> PluginPackage {
+        PluginPackage::from_tar_gz(build_tar_gz(files).as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_schema_is_published() {
+        let published: serde_json::Value = serde_json::from_str(schema(CURRENT_MANIFEST_VERSION).unwrap()).unwrap();
+        assert!(published["properties"]["manifest_version"].is_object());
+        assert!(schema(CURRENT_MANIFEST_VERSION + 1).is_none());
+    }
+
+    #[test]
+    fn test_sample_manifest_is_valid() {
+        let manifest = parse_manifest(INFO_JSON.as_bytes()).unwrap();
+        let package = package_with(&[("info.json", INFO_JSON.as_bytes(), 0o644), ("test.sh", b"echo", 0o755)]);
+
+        assert_eq!(manifest.manifest_version, 1);
+        assert!(validate_manifest(&manifest, &package).is_empty());
+    }
+
+    #[test]
+    fn test_parse_error_has_pointer() {
+        let broken = INFO_JSON.replace("\"executable\": true", "\"executable\": \"yes\"");
+        let issue = parse_manifest(broken.as_bytes()).unwrap_err();
+        assert_eq!(issue.pointer, "/scripts/0/executable");
+    }
+
+    #[test]
+    fn test_validation_reports_every_error() {
+        let mut manifest = parse_manifest(INFO_JSON.as_bytes()).unwrap();
+        manifest.id = "Bad Id".to_string();
+        manifest.version = "1.0".to_string();
+        manifest.scripts[0].name = String::new();
+        let package = package_with(&[("info.json", INFO_JSON.as_bytes(), 0o644)]);
+
+        let pointers: Vec<String> = validate_manifest(&manifest, &package)
+            .into_iter()
+            .map(|issue| issue.pointer)
+            .collect();
+        assert!(pointers.contains(&"/id".to_string()));
+        assert!(pointers.contains(&"/version".to_string()));
+        assert!(pointers.contains(&"/scripts/0/name".to_string()));
+        assert!(pointers.contains(&"/scripts/0/file".to_string())); // missing from package
+    }
+
+    #[test]
+    fn test_hooks_are_checked() {
+        let with_hooks = INFO_JSON.replacen(
+            "\"tags\"",
+            r#""hooks": {"pre_install": "check.sh", "post_install": "setup.sh", "pre_uninstall": "../x.sh"},
+            "tags""#,
+            1,
+        );
+        let manifest = parse_manifest(with_hooks.as_bytes()).unwrap();
+        let package = package_with(&[
+            ("info.json", with_hooks.as_bytes(), 0o644),
+            ("test.sh", b"echo", 0o755),
+            ("scripts/check.sh", b"true", 0o755),
+        ]);
+
+        let pointers: Vec<String> = validate_manifest(&manifest, &package)
+            .into_iter()
+            .map(|issue| issue.pointer)
+            .collect();
+        assert_eq!(pointers, vec!["/hooks/post_install", "/hooks/pre_uninstall"]);
+
+        let typo = INFO_JSON.replacen("\"tags\"", r#""hooks": {"preinstall": "check.sh"}, "tags""#, 1);
+        assert_eq!(parse_manifest(typo.as_bytes()).unwrap_err().pointer, "/hooks/preinstall");
+    }
+
+    #[test]
+    fn test_localizations_are_checked() {
+        let localized = INFO_JSON.replacen(
+            "\"tags\"",
+            r#""default_locale": "zh-CN",
+            "name_i18n": {"en": "System Monitor", "zh-cn": "系统监控", "en_US": "Monitor"},
+            "description_i18n": {"ja": "システム監視"},
+            "tags""#,
+            1,
+        );
+        let manifest = parse_manifest(localized.as_bytes()).unwrap();
+        let package = package_with(&[
+            ("info.json", localized.as_bytes(), 0o644),
+            ("test.sh", b"echo", 0o755),
+            ("README.md", "# 系统监控".as_bytes(), 0o644),
+            ("README.en.md", b"# System Monitor", 0o644),
+            ("readme.EN.md", b"# Again", 0o644),
+        ]);
+
+        let issues = validate_manifest(&manifest, &package);
+        let pointers: Vec<&str> = issues.iter().map(|issue| issue.pointer.as_str()).collect();
+        assert_eq!(pointers, vec!["", "/name_i18n/en_US", "/name_i18n/zh-cn"]);
+        assert!(issues[0].message.starts_with("readme.EN.md"));
+    }
+
+    #[test]
+    fn test_support_links_are_checked() {
+        let with_links = INFO_JSON.replacen(
+            "\"tags\"",
+            r#""issues_url": "http://example.com/issues",
+            "docs_url": "https://docs.example.com",
+            "support_email": "not an email",
+            "funding": [
+                {"type": "github_sponsors", "url": "https://github.com/sponsors/demo"},
+                {"type": "patreon", "url": "https://evil.example.com/patreon"},
+                {"type": "custom", "url": "https://example.com/donate"}
+            ],
+            "tags""#,
+            1,
+        );
+        let manifest = parse_manifest(with_links.as_bytes()).unwrap();
+        let package = package_with(&[("info.json", with_links.as_bytes(), 0o644), ("test.sh", b"echo", 0o755)]);
+
+        let pointers: Vec<String> = validate_manifest(&manifest, &package)
+            .into_iter()
+            .map(|issue| issue.pointer)
+            .collect();
+        assert_eq!(pointers, vec!["/funding/1/url", "/issues_url", "/support_email"]);
+
+        let unknown = INFO_JSON.replacen("\"tags\"", r#""funding": [{"type": "paypal", "url": "https://paypal.me/x"}], "tags""#, 1);
+        assert_eq!(parse_manifest(unknown.as_bytes()).unwrap_err().pointer, "/funding/0/type");
+    }
+
+    #[test]
+    fn test_script_interface_is_checked() {
+        let with_params = INFO_JSON.replacen(
+            "\"executable\": true",
+            r#""executable": true,
+            "parameters": [
+                {"name": "interval", "type": "integer", "default": "5"},
+                {"name": "mode", "type": "string", "enum": ["fast", "full"], "default": "full"},
+                {"name": "mode", "type": "boolean", "required": true, "default": true}
+            ],
+            "exit_codes": [{"code": 0, "description": "ok"}, {"code": 256, "description": "bad"}],
+            "output_formats": ["json", "xml"]"#,
+            1,
+        );
+        let manifest = parse_manifest(with_params.as_bytes()).unwrap();
+        let package = package_with(&[("info.json", with_params.as_bytes(), 0o644), ("test.sh", b"echo", 0o755)]);
+
+        let pointers: Vec<String> = validate_manifest(&manifest, &package)
+            .into_iter()
+            .map(|issue| issue.pointer)
+            .collect();
+        assert_eq!(
+            pointers,
+            vec![
+                "/scripts/0/exit_codes/1/code",
+                "/scripts/0/output_formats/1",
+                "/scripts/0/parameters/0/default",
+                "/scripts/0/parameters/2/default",
+                "/scripts/0/parameters/2/name",
+            ]
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use image::{
+    codecs::jpeg::JpegEncoder, DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits,
+};
+use std::io::Cursor;
+
+use crate::utils::package::{PackageEntry, PluginPackage};
+
+/// Package files that are picked up as the plugin icon, in order of preference.
+pub const ICON_FILES: [&str; 4] = ["icon.png", "icon.jpg", "icon.jpeg", "icon.webp"];
+pub const SCREENSHOT_DIR: &str = "screenshots/";
+pub const MAX_SCREENSHOTS: usize = 10;
+/// Largest request body for uploading one image: a maximum size screenshot plus room for
+/// the multipart framing and the other fields.
+pub const MAX_UPLOAD_BODY: usize = MediaKind::Screenshot.max_bytes() + 64 * 1024;
+
+const IMAGE_EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "webp"];
+const JPEG_QUALITY: u8 = 85;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Icon,
+    Screenshot,
+}
+
+impl MediaKind {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "icon" => Some(Self::Icon),
+            "screenshot" => Some(Self::Screenshot),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Icon => "icon",
+            Self::Screenshot => "screenshot",
+        }
+    }
+
+    const fn max_bytes(&self) -> usize {
+        match self {
+            Self::Icon => 1024 * 1024,
+            Self::Screenshot => 5 * 1024 * 1024,
+        }
+    }
+
+    /// Smallest and largest allowed width or height.
+    fn side_limits(&self) -> (u32, u32) {
+        match self {
+            Self::Icon => (64, 1024),
+            Self::Screenshot => (200, 4096),
+        }
+    }
+
+    /// The box a thumbnail is scaled down to fit.
+    fn thumbnail_size(&self) -> (u32, u32) {
+        match self {
+            Self::Icon => (64, 64),
+            Self::Screenshot => (480, 480),
+        }
+    }
+}
+
+/// A validated image re-encoded without metadata, with its WebP and thumbnail variants.
+#[derive(Debug)]
+pub struct ProcessedImage {
+    /// `png` or `jpg`; WebP uploads are served as PNG with a WebP variant.
+    pub format: &'static str,
+    pub width: u32,
+    pub height: u32,
+    pub full: Vec<u8>,
+    pub full_webp: Vec<u8>,
+    pub thumbnail: Vec<u8>,
+    pub thumbnail_webp: Vec<u8>,
+}
+
+/// The icon and screenshots shipped in a package, screenshots sorted by path.
+pub fn package_media(package: &PluginPackage) -> Vec<(MediaKind, &PackageEntry)> {
+    let icon = ICON_FILES.iter().find_map(|name| package.file(name));
+    let mut screenshots: Vec<&PackageEntry> = package
+        .entries
+        .iter()
+        .filter(|e| {
+            e.path.strip_prefix(SCREENSHOT_DIR).is_some_and(|name| {
+                !name.contains('/')
+                    && name
+                        .rsplit_once('.')
+                        .is_some_and(|(_, ext)| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+            })
+        })
+        .collect();
+    screenshots.sort_by(|a, b| a.path.cmp(&b.path));
+
+    icon.map(|icon| (MediaKind::Icon, icon))
+        .into_iter()
+        .chain(screenshots.into_iter().map(|s| (MediaKind::Screenshot, s)))
+        .collect()
+}
+
+/// Cheap checks that only read the image header: format, file size and dimensions.
+pub fn check_image(data: &[u8], kind: MediaKind) -> Result<(u32, u32), String> {
+    if data.len() > kind.max_bytes() {
+        return Err(format!("Image cannot exceed {} KB", kind.max_bytes() / 1024));
+    }
+    let (width, height) = reader(data)?
+        .into_dimensions()
+        .map_err(|e| format!("Unreadable image: {}", e))?;
+
+    let (min, max) = kind.side_limits();
+    if width < min || height < min || width > max || height > max {
+        return Err(format!(
+            "Image is {}x{}, width and height must be between {} and {} pixels",
+            width, height, min, max
+        ));
+    }
+    if kind == MediaKind::Icon && width != height {
+        return Err(format!("Icons must be square, got {}x{}", width, height));
+    }
+    Ok((width, height))
+}
+
+/// Decode, apply the EXIF orientation, and re-encode every variant. Re-encoding drops
+/// EXIF, XMP and any other embedded metadata.
+pub fn process_image(data: &[u8], kind: MediaKind) -> Result<ProcessedImage, String> {
+    check_image(data, kind)?;
+
+    let mut reader = reader(data)?;
+    let (_, max) = kind.side_limits();
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(max);
+    limits.max_image_height = Some(max);
+    reader.limits(limits);
+
+    let format = reader.format();
+    let mut decoder = reader.into_decoder().map_err(|e| format!("Unreadable image: {}", e))?;
+    let orientation = decoder.orientation().map_err(|e| format!("Unreadable image: {}", e))?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(|e| format!("Unreadable image: {}", e))?;
+    image.apply_orientation(orientation);
+
+    let (thumb_width, thumb_height) = kind.thumbnail_size();
+    let thumbnail = image.thumbnail(thumb_width, thumb_height);
+    let jpeg = format == Some(ImageFormat::Jpeg);
+
+    Ok(ProcessedImage {
+        format: if jpeg { "jpg" } else { "png" },
+        width: image.width(),
+        height: image.height(),
+        full: encode(&image, jpeg)?,
+        full_webp: encode_webp(&image)?,
+        thumbnail: encode(&thumbnail, jpeg)?,
+        thumbnail_webp: encode_webp(&thumbnail)?,
+    })
+}
+
+fn reader(data: &[u8]) -> Result<ImageReader<Cursor<&[u8]>>, String> {
+    let reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|e| format!("Unreadable image: {}", e))?;
+    match reader.format() {
+        Some(ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP) => Ok(reader),
+        _ => Err("Images must be PNG, JPEG or WebP".to_string()),
+    }
+}
+
+fn encode(image: &DynamicImage, jpeg: bool) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let result = if jpeg {
+        // JPEG has no alpha channel
+        JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY).encode_image(&image.to_rgb8())
+    } else {
+        image.write_to(&mut Cursor::new(&mut out), ImageFormat::Png)
+    };
+    result.map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(out)
+}
+
+/// The image crate only writes lossless WebP, which still beats PNG for most icons.
+fn encode_webp(image: &DynamicImage) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    DynamicImage::ImageRgba8(image.to_rgba8())
+        .write_to(&mut Cursor::new(&mut out), ImageFormat::WebP)
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn sample(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let image = RgbImage::from_fn(width, height, |x, y| Rgb([(x % 256) as u8, (y % 256) as u8, 128]));
+        let mut out = Vec::new();
+        DynamicImage::ImageRgb8(image).write_to(&mut Cursor::new(&mut out), format).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_process_icon() {
+        let processed = process_image(&sample(256, 256, ImageFormat::Png), MediaKind::Icon).unwrap();
+        assert_eq!((processed.format, processed.width, processed.height), ("png", 256, 256));
+
+        let thumbnail = image::load_from_memory(&processed.thumbnail_webp).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (64, 64));
+
+        assert!(check_image(&sample(256, 128, ImageFormat::Png), MediaKind::Icon).is_err());
+        assert!(check_image(&sample(32, 32, ImageFormat::Png), MediaKind::Icon).is_err());
+        assert!(check_image(b"GIF89a not really", MediaKind::Icon).is_err());
+    }
+
+    #[test]
+    fn test_exif_is_stripped() {
+        let jpeg = sample(800, 400, ImageFormat::Jpeg);
+        // Splice an APP1 Exif segment in right after the SOI marker
+        let exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\0GPS secret";
+        let mut tagged = jpeg[..2].to_vec();
+        tagged.extend_from_slice(&[0xFF, 0xE1]);
+        tagged.extend_from_slice(&((exif.len() + 2) as u16).to_be_bytes());
+        tagged.extend_from_slice(exif);
+        tagged.extend_from_slice(&jpeg[2..]);
+
+        let processed = process_image(&tagged, MediaKind::Screenshot).unwrap();
+        assert_eq!(processed.format, "jpg");
+        assert!(!processed.full.windows(4).any(|w| w == b"Exif"));
+
+        let thumbnail = image::load_from_memory(&processed.thumbnail).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (480, 240));
+    }
+}
@@ -1,2 +1,13 @@
 pub mod config;
-pub mod validation;
\ No newline at end of file
+pub mod delta;
+pub mod diff;
+pub mod i18n;
+pub mod license;
+pub mod manifest;
+pub mod media;
+pub mod package;
+pub mod pagination;
+pub mod plugin_config;
+pub mod search;
+pub mod validation;
+pub mod version;
\ No newline at end of file
@@ -0,0 +1,410 @@
+use flate2::{read::GzDecoder, Compression, GzBuilder};
+use sha2::{Digest, Sha256};
+use std::{
+    io::{Cursor, Read},
+    path::{Component, Path},
+};
+use tar::{Archive, EntryType};
+
+use crate::models::PluginFileInfo;
+
+/// Per-file hash list embedded in canonical archives, in `sha256sum` format.
+pub const HASH_MANIFEST: &str = "MANIFEST.sha256";
+
+// Guard against decompression bombs; packages are at most 100MB compressed
+const MAX_UNPACKED_SIZE: u64 = 512 * 1024 * 1024;
+
+/// Archive formats accepted for plugin packages, detected from magic bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageFormat {
+    TarGz,
+    TarZst,
+    Zip,
+}
+
+impl PackageFormat {
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        match data {
+            [0x1f, 0x8b, ..] => Some(PackageFormat::TarGz),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(PackageFormat::TarZst),
+            [b'P', b'K', 0x03, 0x04, ..] | [b'P', b'K', 0x05, 0x06, ..] => Some(PackageFormat::Zip),
+            _ => None,
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "tar.gz" => Some(PackageFormat::TarGz),
+            "tar.zst" => Some(PackageFormat::TarZst),
+            "zip" => Some(PackageFormat::Zip),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PackageFormat::TarGz => "tar.gz",
+            PackageFormat::TarZst => "tar.zst",
+            PackageFormat::Zip => "zip",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            PackageFormat::TarGz => "application/gzip",
+            PackageFormat::TarZst => "application/zstd",
+            PackageFormat::Zip => "application/zip",
+        }
+    }
+}
+
+/// A regular file inside a plugin package, relative to the directory holding info.json.
+#[derive(Debug, Clone)]
+pub struct PackageEntry {
+    pub path: String,
+    pub mode: u32,
+    pub data: Vec<u8>,
+}
+
+impl PackageEntry {
+    pub fn is_executable(&self) -> bool {
+        self.mode & 0o111 != 0
+    }
+}
+
+/// The unpacked contents of an uploaded plugin archive, held in memory.
+#[derive(Debug, Clone)]
+pub struct PluginPackage {
+    pub entries: Vec<PackageEntry>,
+}
+
+impl PluginPackage {
+    /// Detect the archive format and unpack it.
+    pub fn from_bytes(data: &[u8]) -> anyhow::Result<(PackageFormat, Self)> {
+        let format = PackageFormat::detect(data).ok_or_else(|| {
+            anyhow::anyhow!("Unsupported package format, expected .zip, .tar.gz or .tar.zst")
+        })?;
+
+        let package = match format {
+            PackageFormat::TarGz => Self::from_tar_gz(data)?,
+            PackageFormat::TarZst => Self::from_tar(zstd::stream::read::Decoder::new(data)?)?,
+            PackageFormat::Zip => Self::from_zip(data)?,
+        };
+
+        Ok((format, package))
+    }
+
+    pub fn from_tar_gz<R: Read>(reader: R) -> anyhow::Result<Self> {
+        Self::from_tar(GzDecoder::new(reader))
+    }
+
+    pub fn from_zip(data: &[u8]) -> anyhow::Result<Self> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(data))?;
+        let mut files = Vec::new();
+        let mut unpacked: u64 = 0;
+
+        for index in 0..archive.len() {
+            let file = archive.by_index(index)?;
+            if file.is_dir() {
+                continue;
+            }
+            if file.is_symlink() {
+                anyhow::bail!("Plugin package contains a link, which is not allowed: {}", file.name());
+            }
+
+            let path = match file.enclosed_name() {
+                Some(path) => normalize_path(&path)?,
+                None => anyhow::bail!("Plugin package contains an unsafe path: {}", file.name()),
+            };
+            // Archives made on Windows carry no unix permissions
+            let mode = file.unix_mode().map(|m| m & 0o7777).unwrap_or(0o644);
+
+            let mut data = Vec::new();
+            file.take(MAX_UNPACKED_SIZE - unpacked).read_to_end(&mut data)?;
+            unpacked += data.len() as u64;
+            if unpacked >= MAX_UNPACKED_SIZE {
+                anyhow::bail!("Plugin package is too large when unpacked");
+            }
+
+            files.push(PackageEntry { path, mode, data });
+        }
+
+        Ok(Self::rooted_at_info_json(files))
+    }
+
+    pub fn from_tar<R: Read>(reader: R) -> anyhow::Result<Self> {
+        let mut archive = Archive::new(reader.take(MAX_UNPACKED_SIZE));
+        let mut files = Vec::new();
+        let mut unpacked: u64 = 0;
+
+        for entry_result in archive.entries()? {
+            let mut entry = entry_result?;
+            let path = normalize_path(&entry.path()?)?;
+
+            match entry.header().entry_type() {
+                EntryType::Regular | EntryType::Continuous => {
+                    let mode = entry.header().mode().unwrap_or(0o644) & 0o7777;
+                    // The size in the header is not trusted for allocation; a few bytes of
+                    // archive can claim terabytes
+                    let mut data = Vec::new();
+                    (&mut entry).take(MAX_UNPACKED_SIZE - unpacked).read_to_end(&mut data)?;
+                    unpacked += data.len() as u64;
+                    if unpacked >= MAX_UNPACKED_SIZE {
+                        anyhow::bail!("Plugin package is too large when unpacked");
+                    }
+                    files.push(PackageEntry { path, mode, data });
+                }
+                EntryType::Symlink | EntryType::Link => {
+                    anyhow::bail!("Plugin package contains a link, which is not allowed: {}", path);
+                }
+                // Directories and pax/GNU metadata carry nothing we need
+                _ => {}
+            }
+        }
+
+        Ok(Self::rooted_at_info_json(files))
+    }
+
+    /// Re-root entries at the shallowest directory containing info.json, so packages
+    /// built with or without a top-level folder look the same.
+    fn rooted_at_info_json(files: Vec<PackageEntry>) -> Self {
+        let root = files
+            .iter()
+            .filter(|e| e.path == "info.json" || e.path.ends_with("/info.json"))
+            .map(|e| e.path.trim_end_matches("info.json").to_string())
+            .min_by_key(|prefix| prefix.matches('/').count());
+
+        let entries = match root {
+            Some(prefix) if !prefix.is_empty() => files
+                .into_iter()
+                .filter_map(|mut e| {
+                    let stripped = e.path.strip_prefix(&prefix)?.to_string();
+                    e.path = stripped;
+                    Some(e)
+                })
+                .collect(),
+            _ => files,
+        };
+
+        Self { entries }
+    }
+
+    pub fn file(&self, path: &str) -> Option<&PackageEntry> {
+        self.entries.iter().find(|e| e.path == path)
+    }
+
+    pub fn info_json(&self) -> Option<&PackageEntry> {
+        self.file("info.json")
+    }
+
+    /// A license text at the package root: LICENSE, LICENCE or COPYING, with any extension.
+    pub fn license_file(&self) -> Option<&PackageEntry> {
+        self.entries.iter().find(|e| {
+            let stem = e.path.split('.').next().unwrap_or_default();
+            !e.path.contains('/') && ["LICENSE", "LICENCE", "COPYING"].iter().any(|name| stem.eq_ignore_ascii_case(name))
+        })
+    }
+
+    /// Scripts may sit next to info.json or in a `scripts/` directory.
+    pub fn script(&self, file: &str) -> Option<&PackageEntry> {
+        self.file(file).or_else(|| self.file(&format!("scripts/{}", file)))
+    }
+
+    pub fn set_executable(&mut self, path: &str) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.path == path) {
+            entry.mode |= 0o111;
+        }
+    }
+
+    /// Hashes of every file except the hash manifest itself, sorted by path.
+    pub fn file_hashes(&self) -> Vec<PluginFileInfo> {
+        let mut files: Vec<PluginFileInfo> = self
+            .entries
+            .iter()
+            .filter(|e| e.path != HASH_MANIFEST)
+            .map(|e| PluginFileInfo {
+                path: e.path.clone(),
+                sha256: hex::encode(Sha256::digest(&e.data)),
+                size: e.data.len() as i64,
+                executable: e.is_executable(),
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        files
+    }
+
+    /// Repack into a reproducible tar.gz: entries sorted by path, timestamps and owners
+    /// zeroed, modes reduced to 0644/0755, a fixed gzip header and a fresh MANIFEST.sha256.
+    /// Identical contents always produce identical bytes.
+    pub fn to_canonical_tar_gz(&self) -> anyhow::Result<Vec<u8>> {
+        let hash_manifest: String = self
+            .file_hashes()
+            .iter()
+            .map(|file| format!("{}  {}\n", file.sha256, file.path))
+            .collect();
+
+        let mut entries: Vec<(&str, u32, &[u8])> = self
+            .entries
+            .iter()
+            .filter(|e| e.path != HASH_MANIFEST)
+            .map(|e| (e.path.as_str(), if e.is_executable() { 0o755 } else { 0o644 }, e.data.as_slice()))
+            .collect();
+        entries.push((HASH_MANIFEST, 0o644, hash_manifest.as_bytes()));
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+
+        let encoder = GzBuilder::new()
+            .mtime(0)
+            .operating_system(255)
+            .write(Vec::new(), Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (path, mode, data) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(mode);
+            header.set_mtime(0);
+            header.set_uid(0);
+            header.set_gid(0);
+            header.set_cksum();
+            builder.append_data(&mut header, path, data)?;
+        }
+        Ok(builder.into_inner()?.finish()?)
+    }
+}
+
+fn normalize_path(path: &Path) -> anyhow::Result<String> {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            Component::CurDir => {}
+            _ => anyhow::bail!("Plugin package contains an unsafe path: {}", path.display()),
+        }
+    }
+    Ok(parts.join("/"))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+
+    pub(crate) fn build_tar_gz(files: &[(&str, &[u8], u32)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, data, mode) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(*mode);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_package_rooted_at_info_json() {
+        let data = build_tar_gz(&[
+            ("demo/info.json", b"{}", 0o644),
+            ("demo/scripts/run.sh", b"echo hi", 0o755),
+            ("demo/docs/info.json", b"{}", 0o644),
+        ]);
+        let package = PluginPackage::from_tar_gz(data.as_slice()).unwrap();
+
+        assert!(package.info_json().is_some());
+        assert!(package.script("run.sh").unwrap().is_executable());
+        assert!(package.file("docs/info.json").is_some());
+    }
+
+    #[test]
+    fn test_detect_formats() {
+        let tar_gz = build_tar_gz(&[("info.json", b"{}", 0o644)]);
+        assert_eq!(PackageFormat::detect(&tar_gz), Some(PackageFormat::TarGz));
+
+        let mut tar = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(2);
+        header.set_cksum();
+        tar.append_data(&mut header, "info.json", &b"{}"[..]).unwrap();
+        let tar_zst = zstd::encode_all(tar.into_inner().unwrap().as_slice(), 0).unwrap();
+        let (format, package) = PluginPackage::from_bytes(&tar_zst).unwrap();
+        assert_eq!(format, PackageFormat::TarZst);
+        assert!(package.info_json().is_some());
+
+        assert!(PluginPackage::from_bytes(b"not an archive").is_err());
+    }
+
+    #[test]
+    fn test_zip_package_converts_to_tar_gz() {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("demo/info.json", options).unwrap();
+        std::io::Write::write_all(&mut zip, b"{}").unwrap();
+        zip.start_file("demo/run.sh", options.unix_permissions(0o755)).unwrap();
+        std::io::Write::write_all(&mut zip, b"echo hi").unwrap();
+        let data = zip.finish().unwrap().into_inner();
+
+        let (format, package) = PluginPackage::from_bytes(&data).unwrap();
+        assert_eq!(format, PackageFormat::Zip);
+        assert!(package.file("run.sh").unwrap().is_executable());
+
+        let repacked = PluginPackage::from_tar_gz(package.to_canonical_tar_gz().unwrap().as_slice()).unwrap();
+        assert_eq!(repacked.file("run.sh").unwrap().data, b"echo hi");
+        assert!(repacked.file("run.sh").unwrap().is_executable());
+    }
+
+    #[test]
+    fn test_canonical_archive_is_reproducible() {
+        let first = build_tar_gz(&[("info.json", b"{}", 0o600), ("run.sh", b"echo hi", 0o775)]);
+        // Same content, different order, modes and a stale hash manifest
+        let second = build_tar_gz(&[
+            ("./pkg/run.sh", b"echo hi", 0o755),
+            ("./pkg/MANIFEST.sha256", b"stale", 0o644),
+            ("./pkg/info.json", b"{}", 0o644),
+        ]);
+
+        let first = PluginPackage::from_tar_gz(first.as_slice()).unwrap().to_canonical_tar_gz().unwrap();
+        let second = PluginPackage::from_tar_gz(second.as_slice()).unwrap().to_canonical_tar_gz().unwrap();
+        assert_eq!(first, second);
+
+        let canonical = PluginPackage::from_tar_gz(first.as_slice()).unwrap();
+        let manifest = String::from_utf8(canonical.file(HASH_MANIFEST).unwrap().data.clone()).unwrap();
+        assert_eq!(manifest.lines().count(), 2);
+        assert!(manifest.contains(&format!("{}  run.sh", hex::encode(Sha256::digest(b"echo hi")))));
+    }
+
+    #[test]
+    fn test_package_rejects_unsafe_paths() {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(2);
+        header.set_mode(0o644);
+        // append_data refuses "..", so write the raw name to simulate a hostile archive
+        header.as_gnu_mut().unwrap().name[..13].copy_from_slice(b"../info.json\0");
+        header.set_cksum();
+        builder.append(&header, &b"{}"[..]).unwrap();
+        let data = builder.into_inner().unwrap().finish().unwrap();
+
+        assert!(PluginPackage::from_tar_gz(data.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_package_ignores_forged_entry_size() {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        let mut header = tar::Header::new_gnu();
+        header.set_path("info.json").unwrap();
+        header.set_size(2);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, &b"{}"[..]).unwrap();
+        // Claim 4TB for an entry whose data is missing
+        let mut forged = tar::Header::new_gnu();
+        forged.set_path("huge.bin").unwrap();
+        forged.set_size(4 << 40);
+        forged.set_mode(0o644);
+        forged.set_cksum();
+        builder.append(&forged, std::io::empty()).unwrap();
+        let data = builder.into_inner().unwrap().finish().unwrap();
+
+        // Fails on the truncated entry instead of allocating what the header claims
+        assert!(PluginPackage::from_tar_gz(data.as_slice()).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+
+/// Position in a sorted plugin listing: the sort key and ID of the row a page starts after,
+/// or ends before. Unlike an offset, it does not shift when rows ahead of it change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    /// Sort field and order the cursor was issued for.
+    #[serde(rename = "s")]
+    pub sort: String,
+    #[serde(rename = "o")]
+    pub order: String,
+    /// Sort key values of the row, as Postgres renders them as text.
+    #[serde(rename = "k")]
+    pub keys: Vec<String>,
+    #[serde(rename = "i")]
+    pub id: String,
+    /// Whether the page ends before the row rather than starting after it.
+    #[serde(rename = "b", default, skip_serializing_if = "std::ops::Not::not")]
+    pub before: bool,
+}
+
+impl Cursor {
+    /// An opaque, URL-safe token.
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// `None` when `token` was not produced by `encode`.
+    pub fn decode(token: &str) -> Option<Self> {
+        let json = URL_SAFE_NO_PAD.decode(token.trim()).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor {
+            sort: "relevance".to_string(),
+            order: "desc".to_string(),
+            keys: vec!["0.0607927".to_string(), "12".to_string()],
+            id: "disk_report".to_string(),
+            before: true,
+        };
+        let token = cursor.encode();
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(Cursor::decode(&token), Some(cursor));
+
+        assert_eq!(Cursor::decode("not a cursor"), None);
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode(b"{\"s\":1}")), None);
+    }
+}
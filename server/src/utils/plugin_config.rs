@@ -0,0 +1,255 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+
+use crate::models::{ConfigBreakingChange, ConfigProperty, ConfigValueType, ManifestIssue, PluginConfigSchema};
+
+const MAX_CONFIG_PROPERTIES: usize = 100;
+
+lazy_static! {
+    static ref CONFIG_KEY_REGEX: Regex = Regex::new(r"^[a-zA-Z_][a-zA-Z0-9_.-]*$").unwrap();
+}
+
+/// Check a manifest's config schema. Pointers are relative to the manifest root.
+pub fn validate_config_schema(schema: &PluginConfigSchema) -> Vec<ManifestIssue> {
+    let mut issues = Vec::new();
+
+    if schema.schema_type.as_deref().is_some_and(|t| t != "object") {
+        issues.push(ManifestIssue::new("/config/type", "The config schema type must be \"object\""));
+    }
+
+    if schema.properties.len() > MAX_CONFIG_PROPERTIES {
+        issues.push(ManifestIssue::new(
+            "/config/properties",
+            format!("Maximum {} config properties allowed", MAX_CONFIG_PROPERTIES),
+        ));
+    }
+
+    for (key, property) in &schema.properties {
+        let pointer = format!("/config/properties/{}", key.replace('~', "~0").replace('/', "~1"));
+        if key.len() > 100 || !CONFIG_KEY_REGEX.is_match(key) {
+            issues.push(ManifestIssue::new(&pointer, format!("Invalid config key: {}", key)));
+        }
+        validate_property(&pointer, property, &mut issues);
+    }
+
+    let mut required = HashSet::new();
+    for (i, key) in schema.required.iter().enumerate() {
+        let pointer = format!("/config/required/{}", i);
+        if !schema.properties.contains_key(key) {
+            issues.push(ManifestIssue::new(pointer, format!("Required config key is not defined: {}", key)));
+        } else if !required.insert(key) {
+            issues.push(ManifestIssue::new(pointer, format!("Duplicate required config key: {}", key)));
+        }
+    }
+
+    issues
+}
+
+fn validate_property(pointer: &str, property: &ConfigProperty, issues: &mut Vec<ManifestIssue>) {
+    let value_type = property.value_type;
+
+    match (&property.items, value_type) {
+        (None, ConfigValueType::Array) => {
+            issues.push(ManifestIssue::new(format!("{}/items", pointer), "Array settings must declare items"));
+        }
+        (Some(items), ConfigValueType::Array) if items.value_type == ConfigValueType::Array => {
+            issues.push(ManifestIssue::new(format!("{}/items/type", pointer), "Nested arrays are not supported"));
+        }
+        (Some(_), ConfigValueType::Array) | (None, _) => {}
+        (Some(_), _) => {
+            issues.push(ManifestIssue::new(format!("{}/items", pointer), "Only array settings can declare items"));
+        }
+    }
+
+    let numeric = matches!(value_type, ConfigValueType::Integer | ConfigValueType::Number);
+    if !numeric && (property.minimum.is_some() || property.maximum.is_some()) {
+        issues.push(ManifestIssue::new(pointer, "Only numeric settings can declare minimum or maximum"));
+    }
+    if let (Some(min), Some(max)) = (bound(&property.minimum), bound(&property.maximum)) {
+        if min > max {
+            issues.push(ManifestIssue::new(format!("{}/minimum", pointer), "Minimum is greater than maximum"));
+        }
+    }
+
+    for (i, choice) in property.choices.iter().enumerate() {
+        if !value_matches(property, choice) {
+            issues.push(ManifestIssue::new(
+                format!("{}/enum/{}", pointer, i),
+                "Choice does not match the setting type",
+            ));
+        }
+    }
+
+    if let Some(default) = &property.default {
+        let pointer = format!("{}/default", pointer);
+        if !value_matches(property, default) {
+            issues.push(ManifestIssue::new(pointer, "Default does not match the setting type"));
+        } else if !property.choices.is_empty() && !property.choices.contains(default) {
+            issues.push(ManifestIssue::new(pointer, "Default must be one of the choices"));
+        } else if !in_range(property, default) {
+            issues.push(ManifestIssue::new(pointer, "Default is outside the allowed range"));
+        }
+    }
+}
+
+fn value_matches(property: &ConfigProperty, value: &Value) -> bool {
+    match (property.value_type, &property.items) {
+        (ConfigValueType::Array, Some(items)) => value
+            .as_array()
+            .is_some_and(|values| values.iter().all(|v| scalar_matches(items.value_type, v))),
+        (ConfigValueType::Array, None) => value.is_array(),
+        (value_type, _) => scalar_matches(value_type, value),
+    }
+}
+
+fn scalar_matches(value_type: ConfigValueType, value: &Value) -> bool {
+    match value_type {
+        ConfigValueType::String => value.is_string(),
+        ConfigValueType::Integer => value.is_i64() || value.is_u64(),
+        ConfigValueType::Number => value.is_number(),
+        ConfigValueType::Boolean => value.is_boolean(),
+        ConfigValueType::Array => false,
+    }
+}
+
+fn in_range(property: &ConfigProperty, value: &Value) -> bool {
+    let Some(number) = value.as_f64() else {
+        return true;
+    };
+    bound(&property.minimum).is_none_or(|min| number >= min) && bound(&property.maximum).is_none_or(|max| number <= max)
+}
+
+fn bound(limit: &Option<serde_json::Number>) -> Option<f64> {
+    limit.as_ref().and_then(|n| n.as_f64())
+}
+
+/// The default value of every setting that has one.
+pub fn defaults(schema: &PluginConfigSchema) -> Map<String, Value> {
+    schema
+        .properties
+        .iter()
+        .filter_map(|(key, property)| property.default.clone().map(|default| (key.clone(), default)))
+        .collect()
+}
+
+/// Changes from `old` to `new` that can invalidate settings saved for the old version:
+/// removed keys, changed types, and keys that became required without a default.
+pub fn breaking_changes(old: &PluginConfigSchema, new: &PluginConfigSchema) -> Vec<ConfigBreakingChange> {
+    let mut changes = Vec::new();
+
+    for (key, old_property) in &old.properties {
+        match new.properties.get(key) {
+            None => changes.push(ConfigBreakingChange {
+                key: key.clone(),
+                change: "removed".to_string(),
+                message: format!("Setting {} was removed", key),
+            }),
+            Some(new_property) if type_name(new_property) != type_name(old_property) => {
+                changes.push(ConfigBreakingChange {
+                    key: key.clone(),
+                    change: "type_changed".to_string(),
+                    message: format!(
+                        "Setting {} changed type from {} to {}",
+                        key,
+                        type_name(old_property),
+                        type_name(new_property)
+                    ),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    for key in &new.required {
+        let has_default = new.properties.get(key).is_some_and(|p| p.default.is_some());
+        if !old.required.contains(key) && !has_default {
+            changes.push(ConfigBreakingChange {
+                key: key.clone(),
+                change: "newly_required".to_string(),
+                message: format!("Setting {} is now required and has no default", key),
+            });
+        }
+    }
+
+    changes
+}
+
+fn type_name(property: &ConfigProperty) -> String {
+    let name = |value_type: ConfigValueType| format!("{:?}", value_type).to_lowercase();
+    match &property.items {
+        Some(items) if property.value_type == ConfigValueType::Array => format!("array<{}>", name(items.value_type)),
+        _ => name(property.value_type),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema(value: Value) -> PluginConfigSchema {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_validate_config_schema() {
+        let config = schema(json!({
+            "type": "object",
+            "properties": {
+                "threshold": {"type": "integer", "default": 150, "minimum": 0, "maximum": 100},
+                "paths": {"type": "array"},
+                "endpoint": {"type": "string", "default": "https://example.com"},
+                "mode": {"type": "string", "enum": ["a", "b"], "default": "c"}
+            },
+            "required": ["endpoint", "missing"]
+        }));
+
+        let pointers: Vec<String> = validate_config_schema(&config).into_iter().map(|i| i.pointer).collect();
+        assert_eq!(
+            pointers,
+            vec![
+                "/config/properties/mode/default",
+                "/config/properties/paths/items",
+                "/config/properties/threshold/default",
+                "/config/required/1",
+            ]
+        );
+        assert_eq!(defaults(&config).len(), 3);
+    }
+
+    #[test]
+    fn test_breaking_changes() {
+        let old = schema(json!({
+            "properties": {
+                "interval": {"type": "integer"},
+                "paths": {"type": "array", "items": {"type": "string"}},
+                "legacy": {"type": "boolean"}
+            }
+        }));
+        let new = schema(json!({
+            "properties": {
+                "interval": {"type": "number"},
+                "paths": {"type": "array", "items": {"type": "string"}},
+                "token": {"type": "string"},
+                "region": {"type": "string", "default": "eu"}
+            },
+            "required": ["token", "region"]
+        }));
+
+        let changes: Vec<(String, String)> = breaking_changes(&old, &new)
+            .into_iter()
+            .map(|c| (c.key, c.change))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("interval".to_string(), "type_changed".to_string()),
+                ("legacy".to_string(), "removed".to_string()),
+                ("token".to_string(), "newly_required".to_string()),
+            ]
+        );
+        assert!(breaking_changes(&new, &new).is_empty());
+    }
+}
@@ -0,0 +1,372 @@
+use pinyin::ToPinyin;
+
+/// Longer normalized queries are cut to this many characters.
+const MAX_NORMALIZED_QUERY: usize = 200;
+/// Longer queries are cut to this many words.
+const MAX_SEARCH_TERMS: usize = 8;
+const MAX_TERM_LENGTH: usize = 50;
+/// Words shown before the first match in a description fragment.
+const FRAGMENT_CONTEXT: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment {
+    Separator,
+    Word,
+    /// A single CJK character.
+    Cjk,
+}
+
+/// Han, kana and Hangul, which are written without spaces between words and need n-grams
+/// to be searchable.
+pub fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{30FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{AC00}'..='\u{D7AF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{20000}'..='\u{2FA1F}'
+    )
+}
+
+/// Lowercased words of a search query, split the way Postgres' default text search parser
+/// splits them, so that `disk_report` or `system-monitor` find both of their parts. CJK
+/// text becomes overlapping bigrams, matching what `cjk_ngrams` indexes.
+pub fn search_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut push = |term: String| {
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+    };
+    for word in query.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+        for (run, cjk) in split_cjk(word) {
+            if !cjk {
+                push(run.to_lowercase().chars().take(MAX_TERM_LENGTH).collect());
+                continue;
+            }
+            let chars: Vec<char> = run.chars().collect();
+            if chars.len() == 1 {
+                push(run.to_string());
+            }
+            for pair in chars.windows(2) {
+                push(pair.iter().collect());
+            }
+        }
+    }
+    terms.truncate(MAX_SEARCH_TERMS);
+    terms
+}
+
+/// `query` as it is logged for search analytics: lowercased, with whitespace collapsed, so
+/// that the same search typed differently counts once.
+pub fn normalize_query(query: &str) -> String {
+    let normalized = query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    normalized.chars().take(MAX_NORMALIZED_QUERY).collect()
+}
+
+/// Every CJK character and pair of adjacent characters in `text`, for the search index.
+/// Postgres does not segment CJK text, so these are stored alongside its own lexemes.
+pub fn cjk_ngrams(text: &str) -> Vec<String> {
+    let mut ngrams = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        for (run, _) in split_cjk(word).into_iter().filter(|(_, cjk)| *cjk) {
+            let chars: Vec<char> = run.chars().collect();
+            for (i, c) in chars.iter().enumerate() {
+                ngrams.push(c.to_string());
+                if let Some(next) = chars.get(i + 1) {
+                    ngrams.push([*c, *next].iter().collect());
+                }
+            }
+        }
+    }
+    ngrams.sort();
+    ngrams.dedup();
+    ngrams
+}
+
+/// A tsquery requiring every term. Words match as prefixes so that partial words typed
+/// into a search box still match; CJK n-grams match exactly. Terms only contain
+/// alphanumerics, so quoting them is enough to keep tsquery operators out.
+pub fn tsquery(terms: &[String]) -> Option<String> {
+    if terms.is_empty() {
+        return None;
+    }
+    let lexemes: Vec<String> = terms
+        .iter()
+        .map(|term| match term.chars().all(is_cjk) {
+            true => format!("'{}'", term),
+            false => format!("'{}':*", term),
+        })
+        .collect();
+    Some(lexemes.join(" & "))
+}
+
+/// Full pinyin and initials of a name containing Chinese characters, e.g. `xitongjiankong`
+/// and `xtjk` for 系统监控. Latin letters and digits are kept whole in both.
+pub fn pinyin_keys(name: &str) -> Option<(String, String)> {
+    let mut full = String::new();
+    let mut initials = String::new();
+    let mut han = false;
+    for c in name.chars() {
+        match c.to_pinyin() {
+            Some(pinyin) => {
+                han = true;
+                full.push_str(pinyin.plain());
+                initials.push_str(pinyin.first_letter());
+            }
+            None if c.is_ascii_alphanumeric() => {
+                full.push(c.to_ascii_lowercase());
+                initials.push(c.to_ascii_lowercase());
+            }
+            None => {}
+        }
+    }
+    han.then_some((full, initials))
+}
+
+/// The pinyin typed into a search box, with syllable separators removed, or `None` when
+/// the query cannot be pinyin.
+pub fn pinyin_query(query: &str) -> Option<String> {
+    let key: String = query
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '\''))
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    (!key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric())).then_some(key)
+}
+
+/// Whether the full pinyin or the initials of `name` start with `key`.
+pub fn pinyin_matches(name: &str, key: &str) -> bool {
+    pinyin_keys(name).is_some_and(|(full, initials)| full.starts_with(key) || initials.starts_with(key))
+}
+
+/// `text` with the LIKE wildcards `%` and `_` escaped, to match literally.
+pub fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// `text`, HTML-escaped, with every word starting with a search term wrapped in `<mark>`.
+/// `None` when nothing matches.
+pub fn highlight(text: &str, terms: &[String]) -> Option<String> {
+    let segments = segments(text);
+    let marks = marks(&segments, terms);
+    marks.contains(&true).then(|| render(&segments, &marks))
+}
+
+/// Like `highlight`, but cut to `max_words` words around the first match, with `…` marking
+/// the cuts. Each CJK character counts as a word.
+pub fn highlight_fragment(text: &str, terms: &[String], max_words: usize) -> Option<String> {
+    let segments = segments(text);
+    let marks = marks(&segments, terms);
+    let words: Vec<usize> = (0..segments.len()).filter(|&i| segments[i].1 != Segment::Separator).collect();
+    let first = words.iter().position(|&i| marks[i])?;
+
+    let start = first.saturating_sub(FRAGMENT_CONTEXT);
+    let end = (start + max_words).min(words.len());
+    let from = if start == 0 { 0 } else { words[start] };
+    let to = if end == words.len() { segments.len() } else { words[end - 1] + 1 };
+
+    let mut fragment = render(&segments[from..to], &marks[from..to]);
+    if from > 0 {
+        fragment.insert_str(0, "… ");
+    }
+    if to < segments.len() {
+        fragment.push_str(" …");
+    }
+    Some(fragment)
+}
+
+/// Like `highlight`, but only the first line that matches with `context` lines either side,
+/// for showing where a script matched.
+pub fn highlight_lines(text: &str, terms: &[String], context: usize) -> Option<String> {
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    let first = lines.iter().position(|line| highlight(line, terms).is_some())?;
+    let from = first.saturating_sub(context);
+    let to = (first + context + 1).min(lines.len());
+    let snippet: Vec<String> = lines[from..to]
+        .iter()
+        .map(|line| highlight(line, terms).unwrap_or_else(|| escape_html(line)))
+        .collect();
+    Some(snippet.join("\n"))
+}
+
+/// `word` split into runs of CJK and other characters, flagged `true` for CJK.
+fn split_cjk(word: &str) -> Vec<(&str, bool)> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    let mut in_cjk = None;
+    for (i, c) in word.char_indices() {
+        let cjk = is_cjk(c);
+        if in_cjk.is_some_and(|current| current != cjk) {
+            runs.push((&word[start..i], !cjk));
+            start = i;
+        }
+        in_cjk = Some(cjk);
+    }
+    if let Some(cjk) = in_cjk {
+        runs.push((&word[start..], cjk));
+    }
+    runs
+}
+
+/// `text` split into words, separators and single CJK characters.
+fn segments(text: &str) -> Vec<(&str, Segment)> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut current = None;
+    for (i, c) in text.char_indices() {
+        let kind = match c {
+            c if is_cjk(c) => Segment::Cjk,
+            c if c.is_alphanumeric() => Segment::Word,
+            _ => Segment::Separator,
+        };
+        if let Some(previous) = current {
+            if previous != kind || kind == Segment::Cjk {
+                segments.push((&text[start..i], previous));
+                start = i;
+            }
+        }
+        current = Some(kind);
+    }
+    if let Some(kind) = current {
+        segments.push((&text[start..], kind));
+    }
+    segments
+}
+
+/// Which segments match: words starting with a term, and runs of CJK characters spelling
+/// out a CJK term.
+fn marks(segments: &[(&str, Segment)], terms: &[String]) -> Vec<bool> {
+    let mut marks = vec![false; segments.len()];
+    for (i, &(text, kind)) in segments.iter().enumerate() {
+        match kind {
+            Segment::Word => {
+                let word = text.to_lowercase();
+                marks[i] |= terms.iter().any(|term| word.starts_with(term.as_str()));
+            }
+            Segment::Cjk => {
+                for term in terms.iter().filter(|term| term.chars().all(is_cjk)) {
+                    let len = term.chars().count();
+                    let Some(run) = segments.get(i..i + len) else {
+                        continue;
+                    };
+                    if run.iter().all(|(_, kind)| *kind == Segment::Cjk)
+                        && run.iter().map(|(text, _)| *text).collect::<String>() == *term
+                    {
+                        marks[i..i + len].fill(true);
+                    }
+                }
+            }
+            Segment::Separator => {}
+        }
+    }
+    marks
+}
+
+fn render(segments: &[(&str, Segment)], marks: &[bool]) -> String {
+    let mut out = String::new();
+    for (i, (text, _)) in segments.iter().enumerate() {
+        if marks[i] && (i == 0 || !marks[i - 1]) {
+            out.push_str("<mark>");
+        }
+        out.push_str(&escape_html(text));
+        if marks[i] && !marks.get(i + 1).copied().unwrap_or(false) {
+            out.push_str("</mark>");
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_terms() {
+        assert_eq!(search_terms("Disk_Report  disk"), vec!["disk", "report"]);
+        assert_eq!(tsquery(&search_terms("sys-mon'; DROP")), Some("'sys':* & 'mon':* & 'drop':*".to_string()));
+        assert_eq!(tsquery(&search_terms("!!!")), None);
+
+        assert_eq!(search_terms("测试插件 cpu监控"), vec!["测试", "试插", "插件", "cpu", "监控"]);
+        assert_eq!(tsquery(&search_terms("系")), Some("'系'".to_string()));
+        assert_eq!(escape_like("50%_off"), "50\\%\\_off");
+    }
+
+    #[test]
+    fn test_normalize_query() {
+        assert_eq!(normalize_query("  Disk\tReport \n"), "disk report");
+        assert_eq!(normalize_query("系统 监控"), "系统 监控");
+        assert_eq!(normalize_query(&"x".repeat(300)).len(), 200);
+    }
+
+    #[test]
+    fn test_cjk_ngrams() {
+        assert_eq!(cjk_ngrams("磁盘报告 disk"), vec!["告", "报", "报告", "盘", "盘报", "磁", "磁盘"]);
+        assert!(cjk_ngrams("Disk report").is_empty());
+        // Every query bigram is in the index
+        let index = cjk_ngrams("一个功能完整的系统监控工具");
+        assert!(search_terms("系统监控").iter().all(|term| index.contains(term)));
+    }
+
+    #[test]
+    fn test_pinyin() {
+        assert_eq!(pinyin_keys("系统监控"), Some(("xitongjiankong".to_string(), "xtjk".to_string())));
+        assert_eq!(pinyin_keys("CPU 监控"), Some(("cpujiankong".to_string(), "cpujk".to_string())));
+        assert_eq!(pinyin_keys("Disk Report"), None);
+
+        assert_eq!(pinyin_query("xi'tong jk"), Some("xitongjk".to_string()));
+        assert_eq!(pinyin_query("系统"), None);
+        assert!(pinyin_matches("系统监控演示插件", "xtjk"));
+        assert!(pinyin_matches("系统监控演示插件", "xitongjian"));
+        assert!(!pinyin_matches("系统监控演示插件", "jiankong"));
+    }
+
+    #[test]
+    fn test_highlight() {
+        let terms = search_terms("disk");
+        assert_eq!(
+            highlight("<b>Disk</b> usage & disks", &terms).unwrap(),
+            "&lt;b&gt;<mark>Disk</mark>&lt;/b&gt; usage &amp; <mark>disks</mark>"
+        );
+        assert_eq!(highlight("Memory report", &terms), None);
+
+        let text = "Shows memory, CPU, network and disk usage for every mounted volume on the host";
+        assert_eq!(
+            highlight_fragment(text, &terms, 6).unwrap(),
+            "… memory, CPU, network and <mark>disk</mark> usage …"
+        );
+        assert_eq!(highlight_fragment("disk usage", &terms, 6).unwrap(), "<mark>disk</mark> usage");
+
+        let terms = search_terms("系统监控");
+        assert_eq!(highlight("系统监控演示", &terms).unwrap(), "<mark>系统监控</mark>演示");
+        assert_eq!(highlight("系统演示", &terms).unwrap(), "<mark>系统</mark>演示");
+    }
+
+    #[test]
+    fn test_highlight_lines() {
+        let script = "#!/bin/sh\nset -e\n\ndf -h | sort   \necho \"<done>\"\nexit 0\n";
+        assert_eq!(
+            highlight_lines(script, &search_terms("df"), 1).unwrap(),
+            "\n<mark>df</mark> -h | sort\necho &quot;&lt;done&gt;&quot;"
+        );
+        assert_eq!(highlight_lines(script, &search_terms("set"), 3).unwrap().lines().count(), 5);
+        assert_eq!(highlight_lines(script, &search_terms("awk"), 1), None);
+    }
+}
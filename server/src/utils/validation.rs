@@ -6,8 +6,17 @@ lazy_static! {
     static ref PLUGIN_ID_REGEX: Regex = Regex::new(r"^[a-z0-9_-]+$").unwrap();
     static ref VERSION_REGEX: Regex = Regex::new(r"^\d+\.\d+\.\d+(-[a-zA-Z0-9]+)?$").unwrap();
     static ref USERNAME_REGEX: Regex = Regex::new(r"^[a-zA-Z0-9_-]+$").unwrap();
+    static ref COMMAND_REGEX: Regex = Regex::new(r"^[a-zA-Z0-9][a-zA-Z0-9._+-]*$").unwrap();
+    static ref PARAMETER_REGEX: Regex = Regex::new(r"^[a-zA-Z][a-zA-Z0-9_-]*$").unwrap();
+    static ref ENV_VAR_REGEX: Regex = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
+    static ref SERVICE_REGEX: Regex = Regex::new(r"^[a-zA-Z0-9][a-zA-Z0-9@._:-]*$").unwrap();
+    static ref LOCALE_REGEX: Regex = Regex::new(r"^[a-zA-Z]{2,3}(-[a-zA-Z0-9]{2,8})*$").unwrap();
 }
 
+pub const SUPPORTED_OS: &[&str] = &["linux", "macos", "windows", "freebsd"];
+pub const SUPPORTED_ARCH: &[&str] = &["x86_64", "aarch64", "x86", "arm", "riscv64"];
+pub const OUTPUT_FORMATS: &[&str] = &["text", "json", "yaml", "csv", "table", "markdown", "html"];
+
 pub fn validate_plugin_id_regex(id: &str) -> bool {
     PLUGIN_ID_REGEX.is_match(id)
 }
@@ -91,6 +100,119 @@ pub fn validate_script_file(filename: &str) -> Result<(), String> {
     Ok(())
 }
 
+pub fn validate_platform(os: &str, arch: Option<&str>) -> Result<(), String> {
+    if !SUPPORTED_OS.contains(&os) {
+        return Err(format!(
+            "Unsupported platform os '{}', expected one of: {}",
+            os,
+            SUPPORTED_OS.join(", ")
+        ));
+    }
+
+    if let Some(arch) = arch {
+        if !SUPPORTED_ARCH.contains(&arch) {
+            return Err(format!(
+                "Unsupported platform arch '{}', expected one of: {}",
+                arch,
+                SUPPORTED_ARCH.join(", ")
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_required_command(command: &str) -> Result<(), String> {
+    if command.len() > 255 || !COMMAND_REGEX.is_match(command) {
+        return Err(format!("Invalid required command: {}", command));
+    }
+
+    Ok(())
+}
+
+pub fn validate_parameter_name(name: &str) -> Result<(), String> {
+    if !PARAMETER_REGEX.is_match(name) {
+        return Err(format!(
+            "Invalid parameter name '{}', use letters, numbers, underscores and hyphens starting with a letter",
+            name
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn validate_output_format(format: &str) -> Result<(), String> {
+    if !OUTPUT_FORMATS.contains(&format) {
+        return Err(format!(
+            "Unsupported output format '{}', expected one of: {}",
+            format,
+            OUTPUT_FORMATS.join(", ")
+        ));
+    }
+
+    Ok(())
+}
+
+/// Filesystem permissions are absolute paths or paths under the user's home (`~/`).
+pub fn validate_permission_path(path: &str) -> Result<(), String> {
+    if path.is_empty() || path.len() > 500 {
+        return Err("Permission path must be between 1 and 500 characters".to_string());
+    }
+
+    if !(path.starts_with('/') || path == "~" || path.starts_with("~/")) {
+        return Err(format!("Permission path must be absolute or start with ~/: {}", path));
+    }
+
+    if path.split('/').any(|part| part == "..") || path.contains('\\') || path.contains('\0') {
+        return Err(format!("Permission path contains invalid components: {}", path));
+    }
+
+    Ok(())
+}
+
+pub fn validate_env_var(name: &str) -> Result<(), String> {
+    if name.len() > 255 || !ENV_VAR_REGEX.is_match(name) {
+        return Err(format!("Invalid environment variable name: {}", name));
+    }
+
+    Ok(())
+}
+
+pub fn validate_service_name(name: &str) -> Result<(), String> {
+    if name.len() > 255 || !SERVICE_REGEX.is_match(name) {
+        return Err(format!("Invalid service name: {}", name));
+    }
+
+    Ok(())
+}
+
+pub fn validate_locale(locale: &str) -> Result<(), String> {
+    if locale.len() > 35 || !LOCALE_REGEX.is_match(locale) {
+        return Err(format!("Invalid locale: {}", locale));
+    }
+
+    Ok(())
+}
+
+/// A public https URL: no credentials, and a domain name rather than an IP address.
+pub fn validate_https_url(value: &str) -> Result<url::Url, String> {
+    if value.len() > 500 {
+        return Err("URL cannot exceed 500 characters".to_string());
+    }
+
+    let url = url::Url::parse(value).map_err(|e| format!("Invalid URL: {}", e))?;
+    if url.scheme() != "https" {
+        return Err("URL must use https".to_string());
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err("URL cannot contain credentials".to_string());
+    }
+    match url.host() {
+        Some(url::Host::Domain(domain)) if domain.contains('.') => Ok(url),
+        _ => Err("URL must point at a public domain name".to_string()),
+    }
+}
+
 pub fn sanitize_filename(filename: &str) -> String {
     filename
         .chars()
@@ -133,4 +255,43 @@ mod tests {
         assert!(validate_script_file("script.txt").is_err()); // invalid extension
         assert!(validate_script_file("../script.sh").is_err()); // path traversal
     }
+
+    #[test]
+    fn test_validate_platform() {
+        assert!(validate_platform("linux", None).is_ok());
+        assert!(validate_platform("macos", Some("aarch64")).is_ok());
+        assert!(validate_platform("Linux", None).is_err()); // case sensitive
+        assert!(validate_platform("linux", Some("amd64")).is_err()); // unknown arch
+    }
+
+    #[test]
+    fn test_validate_required_command() {
+        assert!(validate_required_command("systemctl").is_ok());
+        assert!(validate_required_command("g++").is_ok());
+        assert!(validate_required_command("rm -rf").is_err()); // space
+        assert!(validate_required_command("/usr/bin/brew").is_err()); // path
+    }
+
+    #[test]
+    fn test_validate_permissions() {
+        assert!(validate_permission_path("/etc/hosts").is_ok());
+        assert!(validate_permission_path("~/.config/app").is_ok());
+        assert!(validate_permission_path("relative/path").is_err());
+        assert!(validate_permission_path("/var/../etc/shadow").is_err()); // traversal
+        assert!(validate_env_var("HTTP_PROXY").is_ok());
+        assert!(validate_env_var("1PATH").is_err());
+        assert!(validate_service_name("docker.service").is_ok());
+        assert!(validate_service_name("getty@tty1").is_ok());
+        assert!(validate_service_name("rm -rf").is_err());
+    }
+
+    #[test]
+    fn test_validate_https_url() {
+        assert!(validate_https_url("https://github.com/sponsors/someone").is_ok());
+        assert!(validate_https_url("http://example.com").is_err());
+        assert!(validate_https_url("https://user:pw@example.com").is_err());
+        assert!(validate_https_url("https://127.0.0.1/issues").is_err());
+        assert!(validate_https_url("https://localhost/docs").is_err());
+        assert!(validate_https_url("javascript:alert(1)").is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,131 @@
+use std::cmp::Ordering;
+
+/// Compare two `major.minor.patch[-pre]` versions. A pre-release sorts before its release;
+/// anything unparsable falls back to a plain string comparison.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    match (parse(a), parse(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+/// `(major, minor, patch, is_release, pre)`; the release flag makes 1.0.0-beta < 1.0.0.
+fn parse(version: &str) -> Option<(u64, u64, u64, bool, &str)> {
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let (major, minor, patch) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch, pre.is_none(), pre.unwrap_or("")))
+}
+
+/// A set of comparators that must all hold, such as `>=1.0.0, <2.0.0`. `*` matches anything;
+/// a bare version means exactly that version. `^` and `~` follow the usual caret/tilde rules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionRange {
+    comparators: Vec<(Op, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl VersionRange {
+    pub fn parse(range: &str) -> Result<Self, String> {
+        let range = range.trim();
+        if range == "*" {
+            return Ok(Self { comparators: Vec::new() });
+        }
+
+        let mut comparators = Vec::new();
+        for part in range.split(',').map(str::trim) {
+            let (op, version) = split_operator(part);
+            let Some((major, minor, patch, _, _)) = parse(version) else {
+                return Err(format!("Invalid version range '{}'", range));
+            };
+            match op {
+                "^" | "~" => {
+                    let upper = match (op, major, minor) {
+                        ("^", 0, 0) => format!("0.0.{}", patch + 1),
+                        ("^", 0, _) => format!("0.{}.0", minor + 1),
+                        ("^", _, _) => format!("{}.0.0", major + 1),
+                        _ => format!("{}.{}.0", major, minor + 1),
+                    };
+                    comparators.push((Op::Ge, version.to_string()));
+                    comparators.push((Op::Lt, upper));
+                }
+                _ => {
+                    let op = match op {
+                        ">" => Op::Gt,
+                        ">=" => Op::Ge,
+                        "<" => Op::Lt,
+                        "<=" => Op::Le,
+                        _ => Op::Eq,
+                    };
+                    comparators.push((op, version.to_string()));
+                }
+            }
+        }
+
+        Ok(Self { comparators })
+    }
+
+    pub fn matches(&self, version: &str) -> bool {
+        self.comparators.iter().all(|(op, bound)| {
+            let ordering = compare_versions(version, bound);
+            match op {
+                Op::Eq => ordering == Ordering::Equal,
+                Op::Gt => ordering == Ordering::Greater,
+                Op::Ge => ordering != Ordering::Less,
+                Op::Lt => ordering == Ordering::Less,
+                Op::Le => ordering != Ordering::Greater,
+            }
+        })
+    }
+}
+
+fn split_operator(part: &str) -> (&str, &str) {
+    for op in [">=", "<=", ">", "<", "=", "^", "~"] {
+        if let Some(version) = part.strip_prefix(op) {
+            return (op, version.trim());
+        }
+    }
+    ("=", part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.10.0", "1.9.0"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0.0-beta", "1.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0-alpha", "1.0.0-beta"), Ordering::Less);
+        assert_eq!(compare_versions("2.0.0", "2.0.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_version_range() {
+        let range = VersionRange::parse(">=1.2.0, <2.0.0").unwrap();
+        assert!(range.matches("1.2.0") && range.matches("1.9.9"));
+        assert!(!range.matches("2.0.0") && !range.matches("1.1.0"));
+
+        assert!(VersionRange::parse("^1.4.0").unwrap().matches("1.9.0"));
+        assert!(!VersionRange::parse("^0.3.0").unwrap().matches("0.4.0"));
+        assert!(!VersionRange::parse("~1.4.0").unwrap().matches("1.5.0"));
+        assert!(VersionRange::parse("*").unwrap().matches("0.0.1"));
+        assert!(VersionRange::parse("1.0.0").unwrap().matches("1.0.0"));
+        assert!(VersionRange::parse(">= 1.0").is_err());
+        assert!(VersionRange::parse("").is_err());
+    }
+}